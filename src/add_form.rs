@@ -0,0 +1,224 @@
+use lava_torrent::torrent::v1::Torrent;
+use transmission_rpc::types::TorrentAddArgs;
+
+use crate::app::FilePriority;
+
+pub enum AddRow {
+    DownloadDir,
+    Priority,
+    PeerLimit,
+    Paused,
+    File(usize),
+}
+
+const FIELD_ROWS: usize = 4;
+
+pub struct AddFile {
+    pub name: String,
+    pub length: i64,
+    pub wanted: bool,
+    pub priority: FilePriority,
+}
+
+// Options of the torrent about to be added, files are only known for
+// .torrent files since magnet links have no metadata yet
+pub struct AddForm {
+    pub download_dir: String,
+    pub priority: FilePriority,
+    pub peer_limit: Option<i64>,
+    pub paused: bool,
+    pub files: Vec<AddFile>,
+    pub selected: usize,
+    pub recent_dirs: Vec<String>,
+    used_dirs: Vec<String>,
+    completion: Option<(String, usize)>,
+}
+
+impl AddForm {
+    pub fn new() -> AddForm {
+        AddForm {
+            download_dir: String::new(),
+            priority: FilePriority::Normal,
+            peer_limit: None,
+            paused: false,
+            files: Vec::new(),
+            selected: 0,
+            recent_dirs: Vec::new(),
+            used_dirs: Vec::new(),
+            completion: None,
+        }
+    }
+
+    // Completions are the directories used in this session, the daemon's
+    // default and the directories of existing torrents, most recent first
+    pub fn reset(
+        &mut self,
+        metainfo: Option<&Torrent>,
+        default_dir: Option<String>,
+        torrent_dirs: Vec<String>,
+    ) {
+        self.recent_dirs.clear();
+        for dir in self
+            .used_dirs
+            .iter()
+            .chain(default_dir.iter())
+            .chain(torrent_dirs.iter())
+        {
+            if !self.recent_dirs.contains(dir) {
+                self.recent_dirs.push(dir.to_owned());
+            }
+        }
+
+        self.download_dir = default_dir
+            .or_else(|| self.recent_dirs.first().cloned())
+            .unwrap_or_default();
+        self.priority = FilePriority::Normal;
+        self.peer_limit = None;
+        self.selected = 0;
+        self.completion = None;
+        self.files = match metainfo {
+            Some(torrent) => torrent_files(torrent),
+            None => Vec::new(),
+        };
+    }
+
+    pub fn remember_download_dir(&mut self) {
+        let dir = self.download_dir.trim().to_string();
+        if dir.is_empty() {
+            return;
+        }
+        self.used_dirs.retain(|used| *used != dir);
+        self.used_dirs.insert(0, dir);
+    }
+
+    pub fn row(&self) -> AddRow {
+        match self.selected {
+            0 => AddRow::DownloadDir,
+            1 => AddRow::Priority,
+            2 => AddRow::PeerLimit,
+            3 => AddRow::Paused,
+            index => AddRow::File(index - FIELD_ROWS),
+        }
+    }
+
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % (FIELD_ROWS + self.files.len());
+    }
+
+    pub fn previous(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+        } else {
+            self.selected = FIELD_ROWS + self.files.len() - 1;
+        }
+    }
+
+    pub fn toggle_file(&mut self, index: usize) {
+        if let Some(file) = self.files.get_mut(index) {
+            file.wanted = !file.wanted;
+        }
+    }
+
+    pub fn cycle_priority(&mut self) {
+        self.priority = match self.priority {
+            FilePriority::Low => FilePriority::Normal,
+            FilePriority::Normal => FilePriority::High,
+            FilePriority::High => FilePriority::Low,
+        };
+    }
+
+    // Bandwidth priority on the field rows, the file's priority on a file row
+    pub fn set_priority(&mut self, priority: FilePriority) {
+        match self.row() {
+            AddRow::File(index) => self.files[index].priority = priority,
+            _ => self.priority = priority,
+        }
+    }
+
+    // Repeated calls cycle through the recent directories starting with `input`
+    pub fn complete(&mut self, input: &str) -> Option<String> {
+        let (prefix, index) = match &self.completion {
+            Some((prefix, index)) if self.matches(prefix).get(*index) == Some(&input) => {
+                (prefix.to_owned(), index + 1)
+            }
+            _ => (input.to_string(), 0),
+        };
+
+        let matches = self.matches(&prefix);
+        if matches.is_empty() {
+            return None;
+        }
+        let index = index % matches.len();
+        let completed = matches[index].to_string();
+        self.completion = Some((prefix, index));
+        Some(completed)
+    }
+
+    fn matches(&self, prefix: &str) -> Vec<&str> {
+        self.recent_dirs
+            .iter()
+            .filter(|dir| dir.starts_with(prefix))
+            .map(|dir| dir.as_str())
+            .collect()
+    }
+
+    pub fn args(&self, filename: String) -> TorrentAddArgs {
+        TorrentAddArgs {
+            filename: Some(filename),
+            paused: Some(self.paused),
+            download_dir: match self.download_dir.trim() {
+                "" => None,
+                dir => Some(dir.to_string()),
+            },
+            bandwidth_priority: Some(self.priority.value()),
+            peer_limit: self.peer_limit,
+            files_unwanted: self.file_indices(|file| !file.wanted),
+            priority_high: self.file_indices(|file| matches!(file.priority, FilePriority::High)),
+            priority_low: self.file_indices(|file| matches!(file.priority, FilePriority::Low)),
+            ..TorrentAddArgs::default()
+        }
+    }
+
+    fn file_indices(&self, keep: impl Fn(&AddFile) -> bool) -> Option<Vec<i32>> {
+        let indices: Vec<i32> = (0..self.files.len())
+            .filter(|index| keep(&self.files[*index]))
+            .map(|index| index as i32)
+            .collect();
+
+        if indices.is_empty() {
+            None
+        } else {
+            Some(indices)
+        }
+    }
+}
+
+// The announce-list replaces the announce URL when it's there
+pub fn announce_urls(torrent: &Torrent) -> Vec<String> {
+    match (&torrent.announce_list, &torrent.announce) {
+        (Some(tiers), _) => tiers.iter().flatten().cloned().collect(),
+        (None, Some(announce)) => vec![announce.to_owned()],
+        (None, None) => Vec::new(),
+    }
+}
+
+// Single file torrents have no file list, the torrent itself is the file
+fn torrent_files(torrent: &Torrent) -> Vec<AddFile> {
+    let files: Vec<(String, i64)> = match &torrent.files {
+        Some(files) => files
+            .iter()
+            .map(|file| (file.path.to_string_lossy().to_string(), file.length))
+            .collect(),
+        None => vec![(torrent.name.to_owned(), torrent.length)],
+    };
+
+    files
+        .into_iter()
+        .map(|(name, length)| AddFile {
+            name,
+            length,
+            wanted: true,
+            priority: FilePriority::Normal,
+        })
+        .collect()
+}
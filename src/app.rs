@@ -1,29 +1,45 @@
+use lava_torrent::torrent::v1::Torrent as Metainfo;
 use math::round;
 
 use std::{
-    ffi::OsStr,
-    fs,
-    path::{Path, PathBuf},
-    str::FromStr,
+    collections::{BTreeMap, HashSet},
+    path::PathBuf,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
+use serde_json::{json, Value};
+use tokio::sync::{
+    oneshot::{self, error::TryRecvError},
+    Notify,
+};
 use transmission_rpc::{
     types::{
-        Id, SessionGet, SessionStats, Torrent, TorrentAction, TorrentAddArgs, TorrentGetField,
+        Id, Peer, RpcResponse, RpcResponseArgument, SessionGet, SessionStats, Torrent,
+        TorrentAction, TorrentAddArgs, TorrentGetField, TorrentSetArgs, TrackerStat,
     },
     TransClient,
 };
-use tui::widgets::Row;
+use tui::{
+    style::{Color, Style},
+    widgets::{Cell, Row},
+};
 use tui_tree_widget::TreeState;
 
 use crate::{
-    config::Config,
+    add_form::{announce_urls, AddForm, AddRow},
+    browser::Browser,
+    config::{Config, Connection},
     conversion::{
-        compare_float, compare_int, compare_string, convert_rate, convert_secs, date, get_ratio,
-        get_status_percentage, status_string,
+        compare_float, compare_int, compare_string, convert_bytes, convert_rate, convert_secs,
+        count_string, date, get_ratio, get_status_percentage, status_string, to_key,
     },
+    create::{CreateForm, CreateJob},
+    filter::{Category, Query},
+    magnet::{is_url, read_clipboard, Magnet},
+    preview::{self, Preview},
     tree::{make_tree, StatefulTree},
+    verify::{VerifyJob, VerifyReport},
 };
 
 pub enum RouteId {
@@ -38,8 +54,43 @@ pub struct Route {
 
 pub enum FocusableWidget {
     TorrentList,
+    Sidebar,
     Tabs,
     FileList,
+    PeerList,
+    TrackerList,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum InfoTab {
+    Speed,
+    Files,
+    Peers,
+    Trackers,
+    Pieces,
+    Logs,
+}
+
+impl InfoTab {
+    pub const ALL: [InfoTab; 6] = [
+        InfoTab::Speed,
+        InfoTab::Files,
+        InfoTab::Peers,
+        InfoTab::Trackers,
+        InfoTab::Pieces,
+        InfoTab::Logs,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InfoTab::Speed => "Speed",
+            InfoTab::Files => "Files",
+            InfoTab::Peers => "Peers",
+            InfoTab::Trackers => "Trackers",
+            InfoTab::Pieces => "Pieces",
+            InfoTab::Logs => "Logs",
+        }
+    }
 }
 
 pub enum FloatingWidget {
@@ -48,10 +99,66 @@ pub enum FloatingWidget {
     AddTorrent,
     AddTorrentConfirm,
     RemoveTorrent,
+    RemoveTracker,
     ModifyColumns,
+    Profiles,
+    Filter,
+    SavedFilters,
+    TrackerInput,
+    AddLink,
+    AddDuplicate,
+    AddPreview,
+    CreateSource,
+    CreateTorrent,
+    CreateProgress,
+    VerifyProgress,
+    VerifyResults,
     None,
 }
 
+// A torrent with the same info hash that already is in the daemon
+pub struct Duplicate {
+    pub id: i64,
+    pub name: String,
+    pub status: i64,
+    pub hash: String,
+    pub new_trackers: Vec<String>,
+}
+
+pub enum AddSource {
+    File(PathBuf),
+    Magnet(Magnet),
+    Url(String),
+}
+
+impl AddSource {
+    pub fn from_link(link: &str) -> Result<AddSource, String> {
+        let link = link.trim();
+        if link.starts_with("magnet:") {
+            Magnet::parse(link).map(AddSource::Magnet)
+        } else if is_url(link) {
+            Ok(AddSource::Url(link.to_string()))
+        } else {
+            Err("not a magnet link or http(s) URL".to_string())
+        }
+    }
+
+    // What is sent as `filename` to torrent-add, the daemon fetches URLs itself
+    pub fn filename(&self) -> String {
+        match self {
+            AddSource::File(path) => path.to_string_lossy().to_string(),
+            AddSource::Magnet(magnet) => magnet.uri.to_owned(),
+            AddSource::Url(url) => url.to_owned(),
+        }
+    }
+}
+
+// Where the clipboard contents go once they have been read
+enum Paste {
+    Prefill,
+    Append,
+}
+
 pub enum InputMode {
     Normal,
     Editing,
@@ -69,6 +176,7 @@ pub enum ColumnField {
     UploadRatio,
     DoneDate,
     AddedDate,
+    Daemon,
 }
 
 impl ColumnField {
@@ -84,9 +192,79 @@ impl ColumnField {
             ColumnField::UploadRatio => "Ratio",
             ColumnField::DoneDate => "Date Done",
             ColumnField::AddedDate => "Date Added",
+            ColumnField::Daemon => "Daemon",
         }
         .to_string()
     }
+
+    // Columns whose raw value is a string rather than a number
+    pub fn is_text(&self) -> bool {
+        matches!(self, ColumnField::Name | ColumnField::Daemon)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PeerColumn {
+    Address,
+    Client,
+    Flags,
+    Progress,
+    DownloadRate,
+    UploadRate,
+    Encrypted,
+    Direction,
+}
+
+impl PeerColumn {
+    pub const ALL: [PeerColumn; 8] = [
+        PeerColumn::Address,
+        PeerColumn::Client,
+        PeerColumn::Flags,
+        PeerColumn::Progress,
+        PeerColumn::DownloadRate,
+        PeerColumn::UploadRate,
+        PeerColumn::Encrypted,
+        PeerColumn::Direction,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PeerColumn::Address => "Address",
+            PeerColumn::Client => "Client",
+            PeerColumn::Flags => "Flags",
+            PeerColumn::Progress => "Progress",
+            PeerColumn::DownloadRate => "Down Speed",
+            PeerColumn::UploadRate => "Up Speed",
+            PeerColumn::Encrypted => "Encrypted",
+            PeerColumn::Direction => "Direction",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum FilePriority {
+    High,
+    Normal,
+    Low,
+}
+
+impl FilePriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilePriority::High => "High",
+            FilePriority::Normal => "Normal",
+            FilePriority::Low => "Low",
+        }
+    }
+
+    // Transmission's TR_PRI_* values
+    pub fn value(&self) -> i64 {
+        match self {
+            FilePriority::High => 1,
+            FilePriority::Normal => 0,
+            FilePriority::Low => -1,
+        }
+    }
 }
 
 pub struct ColumnAndShow {
@@ -146,23 +324,146 @@ impl Data {
     }
 }
 
+pub type SharedClient = Arc<tokio::sync::Mutex<TransClient>>;
+
+pub struct Daemon {
+    pub profile: usize,
+    pub client: SharedClient,
+    pub last_error: Option<String>,
+}
+
+fn connect_daemons(config: &Config, profile: usize, aggregated: bool) -> Vec<Daemon> {
+    let profiles = if aggregated {
+        config.aggregate_profiles()
+    } else {
+        vec![profile]
+    };
+
+    profiles
+        .into_iter()
+        .map(|profile| Daemon {
+            profile,
+            client: Arc::new(tokio::sync::Mutex::new(
+                config.profiles[profile].connection.client(),
+            )),
+            last_error: None,
+        })
+        .collect()
+}
+
+pub type RpcResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+const MAX_RETRY_DELAY_SECS: u64 = 60;
+
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    Reconnecting { attempt: u32, last_error: String },
+    Failed(String),
+}
+
+impl ConnectionState {
+    pub fn is_connected(&self) -> bool {
+        matches!(self, ConnectionState::Connected)
+    }
+
+    pub fn retry_delay(&self) -> Duration {
+        match self {
+            ConnectionState::Reconnecting { attempt, .. } => {
+                Duration::from_secs(2_u64.pow(*attempt).min(MAX_RETRY_DELAY_SECS))
+            }
+            ConnectionState::Failed(_) => Duration::from_secs(MAX_RETRY_DELAY_SECS),
+            _ => Duration::from_secs(1),
+        }
+    }
+
+    fn after_error(&self, error: String) -> ConnectionState {
+        let attempt = match self {
+            ConnectionState::Reconnecting { attempt, .. } => attempt + 1,
+            ConnectionState::Failed(_) => return ConnectionState::Failed(error),
+            _ => 1,
+        };
+
+        if attempt > MAX_RECONNECT_ATTEMPTS {
+            ConnectionState::Failed(error)
+        } else {
+            ConnectionState::Reconnecting {
+                attempt,
+                last_error: error,
+            }
+        }
+    }
+
+    pub fn banner(&self, url: &str) -> Option<String> {
+        match self {
+            ConnectionState::Connected => None,
+            ConnectionState::Connecting => Some(format!("Connecting to {}...", url)),
+            ConnectionState::Reconnecting {
+                attempt,
+                last_error,
+            } => Some(format!(
+                "Connection to {} lost: {}. Reconnecting (attempt {}/{})...",
+                url, last_error, attempt, MAX_RECONNECT_ATTEMPTS
+            )),
+            ConnectionState::Failed(error) => Some(format!(
+                "Could not connect to {}: {}. Retrying every {}s",
+                url, error, MAX_RETRY_DELAY_SECS
+            )),
+        }
+    }
+}
+
 pub struct App<'a> {
+    pub daemons: Vec<Daemon>,
+    pub aggregated: bool,
+    pub poll_notify: Arc<Notify>,
+    pub connection_state: ConnectionState,
+    pub active_profile: usize,
+    pub selected_profile: Option<usize>,
     pub session_stats: Option<SessionStats>,
     pub session: Option<SessionGet>,
     pub config: Config,
     pub navigation_stack: Vec<Route>,
     pub torrents: Vec<Torrent>,
+    pub torrent_owners: Vec<usize>,
+    pub visible_torrents: Vec<usize>,
+    pub filter: Option<Query>,
+    pub filter_pattern: String,
+    pub filter_regex: bool,
+    pub filter_error: Option<String>,
+    pub selected_saved_filter: Option<usize>,
+    pub show_sidebar: bool,
+    pub category: Category,
+    pub marked: HashSet<(usize, i64)>,
+    pub visual_anchor: Option<(usize, i64)>,
     pub selected_torrent: Option<usize>,
-    pub selected_tab: usize,
+    pub selected_tab: InfoTab,
+    pub selected_peer: Option<usize>,
+    pub peer_sort_column: PeerColumn,
+    pub peer_sort_descending: bool,
+    pub selected_tracker: Option<usize>,
+    pub editing_tracker: Option<i64>,
     pub floating_widget: FloatingWidget,
     pub should_quit: bool,
     pub sort_descending: bool,
     pub sort_column: ColumnField,
     pub input_mode: InputMode,
     pub input: String,
-    pub torrent_files: Vec<PathBuf>,
-    pub selected_torrent_file: Option<usize>,
-    pub add_paused: bool,
+    pub browser: Browser,
+    pub add_source: Option<AddSource>,
+    pub add_error: Option<String>,
+    pub add_form: AddForm,
+    pub add_duplicate: Option<Duplicate>,
+    pub add_preview: Option<Preview>,
+    pub preview_error: Option<String>,
+    pub preview_scroll: u16,
+    clipboard_read: Option<(Paste, oneshot::Receiver<Option<String>>)>,
+    pub create_form: Option<CreateForm>,
+    pub create_job: Option<CreateJob>,
+    pub verify_job: Option<VerifyJob>,
+    pub verify_report: Option<VerifyReport>,
+    pub selected_verify_file: Option<usize>,
     pub delete_files: bool,
     pub all_info_columns: Vec<ColumnAndShow>,
     pub selected_column: Option<usize>,
@@ -171,27 +472,67 @@ pub struct App<'a> {
 }
 
 impl<'a> App<'a> {
-    pub async fn new() -> App<'a> {
+    pub fn new(config: Config) -> App<'a> {
+        let aggregated = config.start_aggregated;
+        let show_sidebar = config.show_sidebar;
+        let browser_dir = config
+            .torrent_search_dir
+            .to_owned()
+            .or_else(dirs::home_dir)
+            .unwrap_or_else(|| PathBuf::from("/"));
         Self {
+            daemons: connect_daemons(&config, config.default_profile, aggregated),
+            aggregated,
+            poll_notify: Arc::new(Notify::new()),
+            connection_state: ConnectionState::Connecting,
+            active_profile: config.default_profile,
+            selected_profile: Some(config.default_profile),
             session_stats: None,
             session: None,
-            config: Config::new(),
+            config,
             navigation_stack: vec![Route {
                 id: RouteId::TorrentList,
                 focused_widget: FocusableWidget::TorrentList,
             }],
             floating_widget: FloatingWidget::None,
             selected_torrent: Some(0),
-            selected_tab: 0,
+            selected_tab: InfoTab::Speed,
+            selected_peer: Some(0),
+            peer_sort_column: PeerColumn::DownloadRate,
+            peer_sort_descending: true,
+            selected_tracker: Some(0),
+            editing_tracker: None,
             should_quit: false,
             torrents: Vec::new(),
+            torrent_owners: Vec::new(),
+            visible_torrents: Vec::new(),
+            filter: None,
+            filter_pattern: String::new(),
+            filter_regex: false,
+            filter_error: None,
+            selected_saved_filter: Some(0),
+            show_sidebar,
+            category: Category::All,
+            marked: HashSet::new(),
+            visual_anchor: None,
             sort_descending: true,
             sort_column: ColumnField::Name,
             input_mode: InputMode::Normal,
             input: String::new(),
-            torrent_files: vec![],
-            selected_torrent_file: Some(0),
-            add_paused: false,
+            browser: Browser::new(browser_dir),
+            add_source: None,
+            add_error: None,
+            add_form: AddForm::new(),
+            add_duplicate: None,
+            add_preview: None,
+            preview_error: None,
+            preview_scroll: 0,
+            clipboard_read: None,
+            create_form: None,
+            create_job: None,
+            verify_job: None,
+            verify_report: None,
+            selected_verify_file: Some(0),
             delete_files: false,
             all_info_columns: vec![
                 ColumnAndShow {
@@ -234,6 +575,10 @@ impl<'a> App<'a> {
                     column: ColumnField::Id,
                     show: false,
                 },
+                ColumnAndShow {
+                    column: ColumnField::Daemon,
+                    show: aggregated,
+                },
             ],
             selected_column: Some(0),
             tree: StatefulTree::new(),
@@ -241,310 +586,1755 @@ impl<'a> App<'a> {
         }
     }
 
-    pub fn last_route_id(&self) -> Option<&RouteId> {
-        if let Some(i) = self.navigation_stack.last() {
-            Some(&i.id)
+    pub fn connection(&self) -> &Connection {
+        &self.config.profiles[self.active_profile].connection
+    }
+
+    pub fn clients(&self) -> Vec<SharedClient> {
+        self.daemons
+            .iter()
+            .map(|daemon| daemon.client.clone())
+            .collect()
+    }
+
+    pub fn daemon_name(&self, owner: usize) -> &str {
+        &self.config.profiles[self.daemons[owner].profile].name
+    }
+
+    pub fn daemon_label(&self) -> String {
+        if self.aggregated {
+            "all daemons".to_string()
         } else {
-            None
+            self.connection().url()
         }
     }
 
-    pub fn last_route_focused_widget(&self) -> Option<&FocusableWidget> {
-        if let Some(i) = self.navigation_stack.last() {
-            Some(&i.focused_widget)
+    // Torrents are always added to the first daemon, the add dialogs name it
+    // when several daemons are shown
+    pub fn add_target(&self) -> Option<&str> {
+        if self.aggregated {
+            Some(self.daemon_name(0))
         } else {
             None
         }
     }
 
-    pub fn tree_with_path(&mut self) {
-        let torrent = self.get_selected_torrent();
-        let path_str = torrent.download_dir.as_ref().unwrap().to_owned();
-        let mut path = PathBuf::from_str(&path_str).unwrap();
-        let torrent_name_path = PathBuf::from_str(torrent.name.as_ref().unwrap());
-        path = path.join(torrent_name_path.unwrap());
-        let mut skipped_dirs: Vec<PathBuf> = Vec::new();
-        self.tree.items = make_tree(path, self, &mut skipped_dirs);
-        self.tree.state = TreeState::default();
+    pub fn banner(&self) -> Option<String> {
+        if let Some(banner) = self.connection_state.banner(&self.daemon_label()) {
+            return Some(banner);
+        }
+
+        let unreachable: Vec<&str> = (0..self.daemons.len())
+            .filter(|owner| self.daemons[*owner].last_error.is_some())
+            .map(|owner| self.daemon_name(owner))
+            .collect();
+        if unreachable.is_empty() {
+            None
+        } else {
+            Some(format!("Unreachable: {}", unreachable.join(", ")))
+        }
     }
 
-    pub fn next(&mut self) {
-        self.selected_torrent = Some((self.selected_torrent.unwrap() + 1) % self.torrents.len());
-        self.tree_with_path();
+    pub fn next_profile(&mut self) {
+        // The entry after the last profile is the aggregated view
+        self.selected_profile =
+            Some((self.selected_profile.unwrap() + 1) % (self.config.profiles.len() + 1));
     }
 
-    pub fn previous(&mut self) {
-        if self.selected_torrent > Some(0) {
-            self.selected_torrent = Some(self.selected_torrent.unwrap() - 1);
+    pub fn previous_profile(&mut self) {
+        if self.selected_profile > Some(0) {
+            self.selected_profile = Some(self.selected_profile.unwrap() - 1);
         } else {
-            self.selected_torrent = Some(self.torrents.len() - 1);
+            self.selected_profile = Some(self.config.profiles.len());
         }
-        self.tree_with_path();
     }
 
-    pub fn next_column(&mut self) {
-        self.selected_column = Some((self.selected_column.unwrap() + 1) % 10);
-    }
+    pub fn switch_profile(&mut self) {
+        let index = self.selected_profile.unwrap();
+        let aggregated = index == self.config.profiles.len();
+        if aggregated == self.aggregated && (aggregated || index == self.active_profile) {
+            return;
+        }
 
-    pub fn previous_column(&mut self) {
-        if self.selected_column > Some(0) {
-            self.selected_column = Some(self.selected_column.unwrap() - 1);
+        if !aggregated {
+            self.active_profile = index;
+        }
+        self.aggregated = aggregated;
+        self.daemons = connect_daemons(&self.config, self.active_profile, aggregated);
+        self.connection_state = ConnectionState::Connecting;
+        self.navigation_stack.truncate(1);
+        self.torrents.clear();
+        self.torrent_owners.clear();
+        self.visible_torrents.clear();
+        self.marked.clear();
+        self.visual_anchor = None;
+        self.selected_torrent = Some(0);
+        self.session_stats = None;
+        self.session = None;
+        self.data = Data::new();
+        self.build_file_tree();
+        for column in &mut self.all_info_columns {
+            if let ColumnField::Daemon = column.column {
+                column.show = aggregated;
+            }
+        }
+        self.poll_notify.notify_one();
+
+        if aggregated {
+            log::info!("Switched to the aggregated view");
         } else {
-            self.selected_column = Some(9);
+            log::info!("Switched to profile {}", self.config.profiles[index].name);
         }
     }
 
-    pub fn move_column_down(&mut self) {
-        self.all_info_columns.swap(
-            self.selected_column.unwrap(),
-            (self.selected_column.unwrap() + 1) % 10,
-        );
+    pub fn last_route_id(&self) -> Option<&RouteId> {
+        if let Some(i) = self.navigation_stack.last() {
+            Some(&i.id)
+        } else {
+            None
+        }
     }
 
-    pub fn move_column_up(&mut self) {
-        if self.selected_column == Some(0) {
-            self.all_info_columns.swap(self.selected_column.unwrap(), 9);
+    pub fn last_route_focused_widget(&self) -> Option<&FocusableWidget> {
+        if let Some(i) = self.navigation_stack.last() {
+            Some(&i.focused_widget)
         } else {
-            self.all_info_columns.swap(
-                self.selected_column.unwrap() - 1,
-                self.selected_column.unwrap(),
-            );
+            None
         }
     }
 
-    pub fn next_torrent_file(&mut self) {
-        if self.torrent_files.is_empty() {
+    pub fn build_file_tree(&mut self) {
+        if self.visible_torrents.is_empty() {
+            self.tree.items.clear();
+            self.tree.nodes.clear();
+            self.tree.state = TreeState::default();
             return;
         }
 
-        self.selected_torrent_file =
-            Some((self.selected_torrent_file.unwrap() + 1) % self.torrent_files.len());
+        let torrent = self.get_selected_torrent();
+        let download_dir = if self.config.show_missing_files {
+            torrent.download_dir.as_ref().map(PathBuf::from)
+        } else {
+            None
+        };
+        self.tree.nodes = make_tree(torrent, download_dir.as_deref());
+        self.tree.state = TreeState::default();
+        self.refresh_tree();
     }
 
-    pub fn previous_torrent_file(&mut self) {
-        if self.torrent_files.is_empty() {
-            return;
+    pub fn refresh_tree(&mut self) {
+        if let Some(torrent) = self
+            .selected_torrent
+            .and_then(|index| self.torrents.get(index))
+        {
+            self.tree.refresh(torrent);
         }
+    }
 
-        if self.selected_torrent_file > Some(0) {
-            self.selected_torrent_file = Some(self.selected_torrent_file.unwrap() - 1);
-        } else {
-            self.selected_torrent_file = Some(self.torrent_files.len() - 1);
+    fn selected_file_indices(&self) -> RpcResult<Vec<usize>> {
+        self.ensure_selected()?;
+        match self.tree.selected_node() {
+            Some(node) => Ok(node.files.to_owned()),
+            None => Err("no file selected".into()),
         }
     }
 
-    pub fn stack_push(&mut self, route: Route) {
-        self.navigation_stack.push(route);
-    }
+    // Unwanted files are skipped, so a directory with any unwanted file is
+    // made wanted as a whole
+    pub async fn toggle_files_wanted(&mut self) -> RpcResult<()> {
+        let indices = self.selected_file_indices()?;
+        let stats = self
+            .get_selected_torrent()
+            .file_stats
+            .as_ref()
+            .ok_or("no file stats")?;
+        let wanted = indices.iter().all(|index| stats[*index].wanted);
+        let indices = Some(indices.into_iter().map(|index| index as i32).collect());
 
-    pub fn stack_pop(&mut self) {
-        self.navigation_stack.pop();
+        let args = if wanted {
+            TorrentSetArgs {
+                files_unwanted: indices,
+                ..TorrentSetArgs::default()
+            }
+        } else {
+            TorrentSetArgs {
+                files_wanted: indices,
+                ..TorrentSetArgs::default()
+            }
+        };
+        self.set_selected_torrent(args).await
     }
 
-    pub fn next_tab(&mut self) {
-        self.selected_tab = (self.selected_tab + 1) % 3;
-    }
+    pub async fn set_files_priority(&mut self, priority: FilePriority) -> RpcResult<()> {
+        let indices: Vec<i32> = self
+            .selected_file_indices()?
+            .into_iter()
+            .map(|index| index as i32)
+            .collect();
 
-    pub fn previous_tab(&mut self) {
-        if self.selected_tab > 0 {
-            self.selected_tab -= 1;
-        } else {
-            self.selected_tab = 3 - 1;
-        }
+        let args = match priority {
+            FilePriority::High => TorrentSetArgs {
+                priority_high: Some(indices),
+                ..TorrentSetArgs::default()
+            },
+            FilePriority::Normal => TorrentSetArgs {
+                priority_normal: Some(indices),
+                ..TorrentSetArgs::default()
+            },
+            FilePriority::Low => TorrentSetArgs {
+                priority_low: Some(indices),
+                ..TorrentSetArgs::default()
+            },
+        };
+        self.set_selected_torrent(args).await
     }
 
-    pub async fn toggle_torrent_pause(&mut self) {
-        let mut client = TransClient::new("http://localhost:9091/transmission/rpc");
+    pub fn selected_visible_index(&self) -> Option<usize> {
+        self.visible_torrents
+            .iter()
+            .position(|index| Some(*index) == self.selected_torrent)
+    }
 
-        let id = self.torrents[self.selected_torrent.unwrap()].id.unwrap();
+    pub fn next(&mut self) {
+        if self.visible_torrents.is_empty() {
+            return;
+        }
 
-        let status = client
-            .torrent_get(Some(vec![TorrentGetField::Status]), Some(vec![Id::Id(id)]))
-            .await
-            .unwrap()
-            .arguments
-            .torrents[0]
-            .status;
+        let position = self.selected_visible_index().unwrap_or(0);
+        self.selected_torrent =
+            Some(self.visible_torrents[(position + 1) % self.visible_torrents.len()]);
+        self.build_file_tree();
+    }
 
-        let mut action = TorrentAction::Stop;
-        if status == Some(0) {
-            action = TorrentAction::Start;
+    pub fn previous(&mut self) {
+        if self.visible_torrents.is_empty() {
+            return;
         }
 
-        client
-            .torrent_action(
-                action,
-                vec![Id::Id(
-                    self.torrents[self.selected_torrent.unwrap()].id.unwrap(),
-                )],
-            )
-            .await
-            .unwrap();
+        let position = self.selected_visible_index().unwrap_or(0);
+        if position > 0 {
+            self.selected_torrent = Some(self.visible_torrents[position - 1]);
+        } else {
+            self.selected_torrent = self.visible_torrents.last().copied();
+        }
+        self.build_file_tree();
     }
 
-    pub fn get_torrent_rows(&self) -> (Vec<String>, Vec<Row>) {
-        let mut rows = Vec::new();
-        for torrent in &self.torrents {
-            let mut row_strs = Vec::new();
-            for field in &self.all_info_columns {
-                if !field.show {
-                    continue;
-                }
+    pub fn set_filter(&mut self, pattern: &str) {
+        let previous = self.selected_key();
+        self.filter_pattern = pattern.to_string();
+        self.filter_error = None;
+        self.filter = None;
 
-                match field.column {
-                    ColumnField::Name => {
-                        row_strs.push(torrent.name.as_ref().unwrap().to_owned());
-                    }
-                    ColumnField::Id => {
-                        row_strs.push(torrent.id.unwrap().to_string());
-                    }
-                    ColumnField::Eta => {
-                        row_strs.push(convert_secs(torrent.eta.unwrap()));
-                    }
-                    ColumnField::Status => {
-                        row_strs.push(status_string(torrent.status.as_ref().unwrap()).to_string());
-                    }
-                    ColumnField::Progress => {
-                        row_strs.push(get_status_percentage(torrent));
-                    }
-                    ColumnField::DownloadRate => {
-                        row_strs.push(convert_rate(*torrent.rate_download.as_ref().unwrap()));
-                    }
-                    ColumnField::UploadRate => {
-                        row_strs.push(convert_rate(*torrent.rate_upload.as_ref().unwrap()));
-                    }
-                    ColumnField::UploadRatio => {
-                        row_strs.push(format!(
-                            "{:.2}",
-                            get_ratio(torrent.uploaded_ever.unwrap(), torrent.have_valid.unwrap())
-                        ));
-                    }
-                    ColumnField::DoneDate => {
-                        row_strs.push(date(torrent.done_date.unwrap()));
-                    }
-                    ColumnField::AddedDate => {
-                        row_strs.push(date(torrent.added_date.unwrap()));
-                    }
-                }
+        if !pattern.is_empty() {
+            match Query::parse(pattern, self.filter_regex) {
+                Ok(filter) => self.filter = Some(filter),
+                Err(err) => self.filter_error = Some(err),
             }
-            rows.push(Row::new(row_strs));
         }
-        let mut header_rows = Vec::new();
-        for field in &self.all_info_columns {
-            if !field.show {
-                continue;
-            }
-            header_rows.push(field.column.as_str());
+
+        self.apply_filter();
+        if self.selected_key() != previous {
+            self.build_file_tree();
         }
+    }
 
-        (header_rows, rows)
+    pub fn toggle_filter_regex(&mut self) {
+        self.filter_regex = !self.filter_regex;
+        let pattern = self.filter_pattern.to_owned();
+        self.set_filter(&pattern);
     }
 
-    pub fn toggle_show_column(&mut self) {
-        self.all_info_columns[self.selected_column.unwrap()].show =
-            !self.all_info_columns[self.selected_column.unwrap()].show;
+    pub fn next_saved_filter(&mut self) {
+        if self.config.filters.is_empty() {
+            return;
+        }
+
+        self.selected_saved_filter =
+            Some((self.selected_saved_filter.unwrap() + 1) % self.config.filters.len());
     }
 
-    pub async fn rename_torrent(&mut self) {
-        let mut client = TransClient::new("http://localhost:9091/transmission/rpc");
-        client
-            .torrent_rename_path(
-                vec![Id::Id(self.get_selected_torrent_id())],
-                self.get_selected_torrent_name(),
-                self.input.to_owned(),
-            )
-            .await
-            .unwrap();
+    pub fn previous_saved_filter(&mut self) {
+        if self.config.filters.is_empty() {
+            return;
+        }
+
+        if self.selected_saved_filter > Some(0) {
+            self.selected_saved_filter = Some(self.selected_saved_filter.unwrap() - 1);
+        } else {
+            self.selected_saved_filter = Some(self.config.filters.len() - 1);
+        }
     }
 
-    pub async fn add_torrent(&mut self) {
-        let mut client = TransClient::new("http://localhost:9091/transmission/rpc");
-        let add: TorrentAddArgs = TorrentAddArgs {
-            filename: Some(
-                self.torrent_files[self.selected_torrent_file.unwrap()]
-                    .to_str()
-                    .unwrap()
-                    .to_owned(),
-            ),
-            paused: Some(self.add_paused),
-            ..TorrentAddArgs::default()
+    pub fn apply_saved_filter(&mut self) {
+        let query = match self.config.filters.get(self.selected_saved_filter.unwrap()) {
+            Some((_, query)) => query.to_owned(),
+            None => return,
         };
-        client.torrent_add(add).await.unwrap();
+        self.filter_regex = false;
+        self.set_filter(&query);
     }
 
-    pub async fn remove_torrent(&mut self) {
-        let mut client = TransClient::new("http://localhost:9091/transmission/rpc");
-        client
-            .torrent_remove(
-                vec![Id::Id(self.get_selected_torrent_id())],
-                self.delete_files,
-            )
-            .await
+    pub fn next_category(&mut self) {
+        let index = Category::ALL
+            .iter()
+            .position(|category| *category == self.category)
             .unwrap();
+        self.set_category(Category::ALL[(index + 1) % Category::ALL.len()]);
     }
 
-    pub async fn verify_torrent(&mut self) {
-        let mut client = TransClient::new("http://localhost:9091/transmission/rpc");
-        client
-            .torrent_action(
-                TorrentAction::Verify,
-                vec![Id::Id(self.get_selected_torrent_id())],
-            )
-            .await
+    pub fn previous_category(&mut self) {
+        let index = Category::ALL
+            .iter()
+            .position(|category| *category == self.category)
             .unwrap();
+        if index > 0 {
+            self.set_category(Category::ALL[index - 1]);
+        } else {
+            self.set_category(Category::ALL[Category::ALL.len() - 1]);
+        }
     }
 
-    pub fn get_torrent_files(&mut self) {
-        self.torrent_files.clear();
+    fn set_category(&mut self, category: Category) {
+        let previous = self.selected_key();
+        self.category = category;
+        self.apply_filter();
+        if self.selected_key() != previous {
+            self.build_file_tree();
+        }
+    }
+
+    pub fn category_count(&self, category: Category) -> usize {
+        self.torrents
+            .iter()
+            .filter(|torrent| category.matches(torrent))
+            .count()
+    }
 
-        if let Some(dir) = &self.config.torrent_search_dir {
-            if let Ok(paths) = fs::read_dir(dir) {
-                for path in paths {
-                    let path = path.unwrap().path();
-                    let file = Path::new(&path);
-                    if file.extension().and_then(OsStr::to_str) == Some("torrent") {
-                        self.torrent_files.push(file.to_path_buf());
+    pub fn apply_filter(&mut self) {
+        self.visible_torrents = (0..self.torrents.len())
+            .filter(|index| {
+                let torrent = &self.torrents[*index];
+                self.category.matches(torrent)
+                    && match &self.filter {
+                        Some(filter) => filter.matches(torrent),
+                        None => true,
                     }
-                }
-            }
+            })
+            .collect();
+
+        if self.selected_visible_index().is_none() {
+            self.selected_torrent = Some(self.visible_torrents.first().copied().unwrap_or(0));
         }
     }
 
-    pub fn toggle_add_torrent_paused(&mut self) {
-        self.add_paused = !self.add_paused;
+    fn selected_key(&self) -> Option<(usize, i64)> {
+        let index = self.selected_torrent?;
+        self.torrents.get(index)?;
+        Some(self.torrent_key(index))
     }
 
-    fn get_selected_torrent_id(&self) -> i64 {
-        self.torrents[self.selected_torrent.unwrap()].id.unwrap()
+    fn torrent_key(&self, index: usize) -> (usize, i64) {
+        (self.torrent_owners[index], self.torrents[index].id.unwrap())
     }
 
-    pub fn get_selected_torrent(&self) -> &Torrent {
-        &self.torrents[self.selected_torrent.unwrap()]
+    pub fn toggle_mark(&mut self) {
+        if self.visible_torrents.is_empty() {
+            return;
+        }
+
+        let key = self.selected_key().unwrap();
+        if !self.marked.remove(&key) {
+            self.marked.insert(key);
+        }
+        self.next();
     }
 
-    pub fn get_selected_torrent_name(&self) -> String {
-        self.torrents[self.selected_torrent.unwrap()]
-            .name
-            .to_owned()
-            .unwrap()
+    // Leaving visual mode marks every torrent in the range
+    pub fn toggle_visual(&mut self) {
+        if self.visual_anchor.is_some() {
+            let range = self.visual_range();
+            self.marked.extend(range);
+            self.visual_anchor = None;
+        } else {
+            self.visual_anchor = self.selected_key();
+        }
     }
-}
 
-pub async fn get_all_torrents<'a>(app: &Arc<Mutex<App<'a>>>) {
-    let mut client = TransClient::new("http://localhost:9091/transmission/rpc");
-    let mut torrents = client
-        .torrent_get(None, None)
-        .await
-        .unwrap()
-        .arguments
-        .torrents;
-    let session_stats = client.session_stats().await.unwrap().arguments;
-    let session = client.session_get().await.unwrap().arguments;
+    // Marks every torrent matching the current filter, or unmarks them if they
+    // are all marked already
+    pub fn toggle_mark_all(&mut self) {
+        let keys: Vec<(usize, i64)> = self
+            .visible_torrents
+            .iter()
+            .map(|index| self.torrent_key(*index))
+            .collect();
 
-    let mut app = app.lock().unwrap();
+        if keys.iter().all(|key| self.marked.contains(key)) {
+            for key in keys {
+                self.marked.remove(&key);
+            }
+        } else {
+            self.marked.extend(keys);
+        }
+    }
 
-    torrents.sort_by(|a, b| match app.sort_column {
-        ColumnField::Id => compare_int(a.id.unwrap(), b.id.unwrap()),
+    pub fn clear_marks(&mut self) -> bool {
+        if self.visual_anchor.take().is_some() {
+            return true;
+        }
+        if self.marked.is_empty() {
+            return false;
+        }
+        self.marked.clear();
+        true
+    }
+
+    fn visual_range(&self) -> Vec<(usize, i64)> {
+        let anchor = match self.visual_anchor {
+            Some(anchor) => anchor,
+            None => return Vec::new(),
+        };
+        let start = self
+            .visible_torrents
+            .iter()
+            .position(|index| self.torrent_key(*index) == anchor);
+        let (start, end) = match (start, self.selected_visible_index()) {
+            (Some(start), Some(end)) => (start.min(end), start.max(end)),
+            _ => return Vec::new(),
+        };
+
+        self.visible_torrents[start..=end]
+            .iter()
+            .map(|index| self.torrent_key(*index))
+            .collect()
+    }
+
+    pub fn marked_keys(&self) -> HashSet<(usize, i64)> {
+        let mut keys = self.marked.clone();
+        keys.extend(self.visual_range());
+        keys
+    }
+
+    // Marked torrents, or the selected one when nothing is marked, grouped by
+    // daemon so each daemon gets a single request
+    fn targets(&self) -> Vec<(SharedClient, Vec<Id>)> {
+        let mut keys = self.marked_keys();
+        if keys.is_empty() {
+            keys.extend(self.selected_key());
+        }
+
+        let mut ids: BTreeMap<usize, Vec<Id>> = BTreeMap::new();
+        for (owner, id) in keys {
+            ids.entry(owner).or_default().push(Id::Id(id));
+        }
+
+        ids.into_iter()
+            .map(|(owner, ids)| (self.daemons[owner].client.clone(), ids))
+            .collect()
+    }
+
+    fn select_key(&mut self, key: Option<(usize, i64)>) {
+        if let Some((owner, id)) = key {
+            if let Some(index) = (0..self.torrents.len()).find(|index| {
+                self.torrent_owners[*index] == owner && self.torrents[*index].id == Some(id)
+            }) {
+                self.selected_torrent = Some(index);
+            }
+        }
+    }
+
+    pub fn next_column(&mut self) {
+        self.selected_column =
+            Some((self.selected_column.unwrap() + 1) % self.all_info_columns.len());
+    }
+
+    pub fn previous_column(&mut self) {
+        if self.selected_column > Some(0) {
+            self.selected_column = Some(self.selected_column.unwrap() - 1);
+        } else {
+            self.selected_column = Some(self.all_info_columns.len() - 1);
+        }
+    }
+
+    pub fn move_column_down(&mut self) {
+        let len = self.all_info_columns.len();
+        self.all_info_columns.swap(
+            self.selected_column.unwrap(),
+            (self.selected_column.unwrap() + 1) % len,
+        );
+    }
+
+    pub fn move_column_up(&mut self) {
+        if self.selected_column == Some(0) {
+            let last = self.all_info_columns.len() - 1;
+            self.all_info_columns
+                .swap(self.selected_column.unwrap(), last);
+        } else {
+            self.all_info_columns.swap(
+                self.selected_column.unwrap() - 1,
+                self.selected_column.unwrap(),
+            );
+        }
+    }
+
+    pub fn stack_push(&mut self, route: Route) {
+        self.navigation_stack.push(route);
+    }
+
+    pub fn stack_pop(&mut self) {
+        self.navigation_stack.pop();
+    }
+
+    pub fn next_tab(&mut self) {
+        let index = self.selected_tab_index();
+        self.selected_tab = InfoTab::ALL[(index + 1) % InfoTab::ALL.len()];
+    }
+
+    pub fn previous_tab(&mut self) {
+        let index = self.selected_tab_index();
+        if index > 0 {
+            self.selected_tab = InfoTab::ALL[index - 1];
+        } else {
+            self.selected_tab = InfoTab::ALL[InfoTab::ALL.len() - 1];
+        }
+    }
+
+    pub fn selected_tab_index(&self) -> usize {
+        InfoTab::ALL
+            .iter()
+            .position(|tab| *tab == self.selected_tab)
+            .unwrap()
+    }
+
+    fn peer_count(&self) -> usize {
+        self.selected_torrent
+            .and_then(|index| self.torrents.get(index))
+            .and_then(|torrent| torrent.peers.as_ref())
+            .map_or(0, Vec::len)
+    }
+
+    pub fn next_peer(&mut self) {
+        let count = self.peer_count();
+        if count == 0 {
+            return;
+        }
+
+        self.selected_peer = Some((self.selected_peer.unwrap_or(0) + 1) % count);
+    }
+
+    pub fn previous_peer(&mut self) {
+        let count = self.peer_count();
+        if count == 0 {
+            return;
+        }
+
+        match self.selected_peer {
+            Some(index) if index > 0 && index < count => self.selected_peer = Some(index - 1),
+            _ => self.selected_peer = Some(count - 1),
+        }
+    }
+
+    pub fn next_peer_sort_column(&mut self) {
+        let index = PeerColumn::ALL
+            .iter()
+            .position(|column| *column == self.peer_sort_column)
+            .unwrap();
+        self.peer_sort_column = PeerColumn::ALL[(index + 1) % PeerColumn::ALL.len()];
+    }
+
+    pub fn previous_peer_sort_column(&mut self) {
+        let index = PeerColumn::ALL
+            .iter()
+            .position(|column| *column == self.peer_sort_column)
+            .unwrap();
+        if index > 0 {
+            self.peer_sort_column = PeerColumn::ALL[index - 1];
+        } else {
+            self.peer_sort_column = PeerColumn::ALL[PeerColumn::ALL.len() - 1];
+        }
+    }
+
+    pub fn toggle_peer_sort_order(&mut self) {
+        self.peer_sort_descending = !self.peer_sort_descending;
+    }
+
+    // Trackers of the selected torrent ordered by tier
+    pub fn tracker_stats(&self) -> Vec<&TrackerStat> {
+        let mut stats: Vec<&TrackerStat> = match self
+            .selected_torrent
+            .and_then(|index| self.torrents.get(index))
+            .and_then(|torrent| torrent.tracker_stats.as_ref())
+        {
+            Some(stats) => stats.iter().collect(),
+            None => return Vec::new(),
+        };
+        stats.sort_by_key(|stat| (stat.tier, stat.id));
+        stats
+    }
+
+    pub fn selected_tracker_stat(&self) -> Option<&TrackerStat> {
+        self.tracker_stats().get(self.selected_tracker?).copied()
+    }
+
+    pub fn next_tracker(&mut self) {
+        let count = self.tracker_stats().len();
+        if count == 0 {
+            return;
+        }
+
+        self.selected_tracker = Some((self.selected_tracker.unwrap_or(0) + 1) % count);
+    }
+
+    pub fn previous_tracker(&mut self) {
+        let count = self.tracker_stats().len();
+        if count == 0 {
+            return;
+        }
+
+        match self.selected_tracker {
+            Some(index) if index > 0 && index < count => self.selected_tracker = Some(index - 1),
+            _ => self.selected_tracker = Some(count - 1),
+        }
+    }
+
+    // Rows are flagged when the last announce failed
+    pub fn get_tracker_rows(&self) -> Vec<(bool, Vec<String>)> {
+        self.tracker_stats()
+            .into_iter()
+            .map(|stat| {
+                let failed = stat.has_announced && !stat.last_announce_succeeded;
+                let row = vec![
+                    (stat.tier + 1).to_string(),
+                    stat.announce.to_owned(),
+                    date(stat.last_announce_time),
+                    stat.last_announce_result.to_owned(),
+                    date(stat.last_scrape_time),
+                    stat.last_scrape_result.to_owned(),
+                    count_string(stat.seeder_count),
+                    count_string(stat.leecher_count),
+                    date(stat.next_announce_time),
+                ];
+                (failed, row)
+            })
+            .collect()
+    }
+
+    pub async fn add_tracker(&mut self) -> RpcResult<()> {
+        self.ensure_selected()?;
+        self.set_selected_torrent(TorrentSetArgs {
+            tracker_add: Some(vec![self.input.trim().to_owned()]),
+            ..TorrentSetArgs::default()
+        })
+        .await
+    }
+
+    // The whole list is sent again so the tracker keeps its tier
+    pub async fn replace_tracker(&mut self) -> RpcResult<()> {
+        self.ensure_selected()?;
+        let mut list = Vec::new();
+        let mut tier = None;
+        for stat in self.tracker_stats() {
+            if tier.is_some() && tier != Some(stat.tier) {
+                list.push(String::new());
+            }
+            tier = Some(stat.tier);
+
+            if Some(stat.id) == self.editing_tracker {
+                list.push(self.input.trim().to_owned());
+            } else {
+                list.push(stat.announce.to_owned());
+            }
+        }
+
+        self.set_selected_torrent(TorrentSetArgs {
+            tracker_list: Some(list),
+            ..TorrentSetArgs::default()
+        })
+        .await
+    }
+
+    pub async fn remove_tracker(&mut self) -> RpcResult<()> {
+        self.ensure_selected()?;
+        let id = match self.selected_tracker_stat() {
+            Some(stat) => stat.id,
+            None => return Err("no tracker selected".into()),
+        };
+        self.set_selected_torrent(TorrentSetArgs {
+            tracker_remove: Some(vec![id]),
+            ..TorrentSetArgs::default()
+        })
+        .await
+    }
+
+    async fn set_selected_torrent(&self, args: TorrentSetArgs) -> RpcResult<()> {
+        let client = self.selected_client();
+        let mut client = client.lock().await;
+        check(
+            client
+                .torrent_set(args, Some(vec![Id::Id(self.get_selected_torrent_id())]))
+                .await?,
+        )?;
+        self.poll_notify.notify_one();
+        Ok(())
+    }
+
+    pub async fn reannounce_torrent(&mut self) -> RpcResult<()> {
+        self.ensure_selected()?;
+        let client = self.selected_client();
+        let mut client = client.lock().await;
+        check(
+            client
+                .torrent_action(
+                    TorrentAction::Reannounce,
+                    vec![Id::Id(self.get_selected_torrent_id())],
+                )
+                .await?,
+        )?;
+        Ok(())
+    }
+
+    // Peers of the selected torrent, sorted by `peer_sort_column`
+    pub fn get_peer_rows(&self) -> Vec<Vec<String>> {
+        let mut peers: Vec<&Peer> = match self
+            .selected_torrent
+            .and_then(|index| self.torrents.get(index))
+            .and_then(|torrent| torrent.peers.as_ref())
+        {
+            Some(peers) => peers.iter().collect(),
+            None => return Vec::new(),
+        };
+
+        peers.sort_by(|a, b| match self.peer_sort_column {
+            PeerColumn::Address => compare_string(&a.address, &b.address),
+            PeerColumn::Client => compare_string(&a.client_name, &b.client_name),
+            PeerColumn::Flags => compare_string(&a.flag_str, &b.flag_str),
+            PeerColumn::Progress => compare_float(a.progress, b.progress),
+            PeerColumn::DownloadRate => compare_int(a.rate_to_client, b.rate_to_client),
+            PeerColumn::UploadRate => compare_int(a.rate_to_peer, b.rate_to_peer),
+            PeerColumn::Encrypted => a.is_encrypted.cmp(&b.is_encrypted),
+            PeerColumn::Direction => a.is_incoming.cmp(&b.is_incoming),
+        });
+        if self.peer_sort_descending {
+            peers.reverse();
+        }
+
+        peers
+            .into_iter()
+            .map(|peer| {
+                PeerColumn::ALL
+                    .iter()
+                    .map(|column| match column {
+                        PeerColumn::Address => format!("{}:{}", peer.address, peer.port),
+                        PeerColumn::Client => peer.client_name.to_owned(),
+                        PeerColumn::Flags => peer.flag_str.to_owned(),
+                        PeerColumn::Progress => format!("{:.1}%", peer.progress * 100.0),
+                        PeerColumn::DownloadRate => convert_rate(peer.rate_to_client),
+                        PeerColumn::UploadRate => convert_rate(peer.rate_to_peer),
+                        PeerColumn::Encrypted if peer.is_encrypted => "Yes".to_string(),
+                        PeerColumn::Encrypted => "No".to_string(),
+                        PeerColumn::Direction if peer.is_incoming => "Incoming".to_string(),
+                        PeerColumn::Direction => "Outgoing".to_string(),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // Resumes the torrents if all of them are stopped, otherwise stops them
+    pub async fn toggle_torrent_pause(&mut self) -> RpcResult<()> {
+        self.ensure_selected()?;
+        let targets = self.targets();
+
+        let mut stopped = true;
+        for (client, ids) in &targets {
+            let mut client = client.lock().await;
+            let torrents = check(
+                client
+                    .torrent_get(Some(vec![TorrentGetField::Status]), Some(ids.clone()))
+                    .await?,
+            )?
+            .torrents;
+            stopped &= torrents.iter().all(|torrent| torrent.status == Some(0));
+        }
+
+        let mut action = TorrentAction::Stop;
+        if stopped {
+            action = TorrentAction::Start;
+        }
+
+        for (client, ids) in targets {
+            let mut client = client.lock().await;
+            check(client.torrent_action(action, ids).await?)?;
+        }
+        Ok(())
+    }
+
+    pub async fn torrent_action(&mut self, action: TorrentAction) -> RpcResult<()> {
+        self.ensure_selected()?;
+        for (client, ids) in self.targets() {
+            let mut client = client.lock().await;
+            check(client.torrent_action(action, ids).await?)?;
+        }
+        Ok(())
+    }
+
+    // Ids are only unique per daemon, so in the aggregated view `profile`
+    // picks one when several of them have a torrent with this id
+    pub fn select_torrent_by_id(&mut self, profile: Option<&str>, id: i64) -> Result<(), String> {
+        let matches: Vec<usize> = (0..self.torrents.len())
+            .filter(|index| self.torrents[*index].id == Some(id))
+            .filter(|index| match profile {
+                Some(profile) => self.daemon_name(self.torrent_owners[*index]) == profile,
+                None => true,
+            })
+            .collect();
+
+        match matches[..] {
+            [index] => {
+                self.selected_torrent = Some(index);
+                Ok(())
+            }
+            [] => Err(match profile {
+                Some(profile) => format!("no torrent with id {} on {}", id, profile),
+                None => format!("no torrent with id {}", id),
+            }),
+            _ => {
+                let names: Vec<&str> = matches
+                    .iter()
+                    .map(|index| self.daemon_name(self.torrent_owners[*index]))
+                    .collect();
+                Err(format!(
+                    "torrent id {} exists on {}, use <profile>:{}",
+                    id,
+                    names.join(", "),
+                    id
+                ))
+            }
+        }
+    }
+
+    pub fn get_torrent_rows(&self) -> (Vec<String>, Vec<Row<'_>>) {
+        let (header_rows, rows) = self.get_torrent_strings();
+        let name_column = self
+            .all_info_columns
+            .iter()
+            .filter(|field| field.show)
+            .position(|field| matches!(field.column, ColumnField::Name));
+
+        let marked = self.marked_keys();
+        let rows = rows
+            .into_iter()
+            .zip(&self.visible_torrents)
+            .map(|(row, index)| {
+                let cells: Vec<Cell> = row
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, text)| match &self.filter {
+                        Some(filter) if Some(i) == name_column => {
+                            Cell::from(filter.highlight(text))
+                        }
+                        _ => Cell::from(text),
+                    })
+                    .collect();
+                if marked.contains(&self.torrent_key(*index)) {
+                    Row::new(cells).style(Style::default().fg(Color::Yellow))
+                } else {
+                    Row::new(cells)
+                }
+            })
+            .collect();
+
+        (header_rows, rows)
+    }
+
+    pub fn get_torrent_strings(&self) -> (Vec<String>, Vec<Vec<String>>) {
+        let (header_rows, rows) = self.get_torrent_values();
+        let rows = rows
+            .into_iter()
+            .map(|row| row.into_iter().map(|(_, human)| human).collect())
+            .collect();
+        (header_rows, rows)
+    }
+
+    pub fn get_torrent_values(&self) -> (Vec<String>, Vec<Vec<(Value, String)>>) {
+        let mut rows = Vec::new();
+        for i in self.visible_torrents.iter().copied() {
+            let torrent = &self.torrents[i];
+            let mut row_values = Vec::new();
+            for field in &self.all_info_columns {
+                if !field.show {
+                    continue;
+                }
+
+                match field.column {
+                    ColumnField::Name => {
+                        let name = torrent.name.as_ref().unwrap().to_owned();
+                        row_values.push((json!(name), name));
+                    }
+                    ColumnField::Id => {
+                        let id = torrent.id.unwrap();
+                        row_values.push((json!(id), id.to_string()));
+                    }
+                    ColumnField::Eta => {
+                        let eta = torrent.eta.unwrap();
+                        row_values.push((json!(eta), convert_secs(eta)));
+                    }
+                    ColumnField::Status => {
+                        let status = torrent.status.unwrap();
+                        row_values.push((json!(status), status_string(&status).to_string()));
+                    }
+                    ColumnField::Progress => {
+                        let progress = match torrent.status {
+                            Some(2) => torrent.recheck_progress.unwrap(),
+                            _ => torrent.percent_done.unwrap(),
+                        };
+                        row_values.push((json!(progress), get_status_percentage(torrent)));
+                    }
+                    ColumnField::DownloadRate => {
+                        let rate = torrent.rate_download.unwrap();
+                        row_values.push((json!(rate), convert_rate(rate)));
+                    }
+                    ColumnField::UploadRate => {
+                        let rate = torrent.rate_upload.unwrap();
+                        row_values.push((json!(rate), convert_rate(rate)));
+                    }
+                    ColumnField::UploadRatio => {
+                        let ratio =
+                            get_ratio(torrent.uploaded_ever.unwrap(), torrent.have_valid.unwrap());
+                        row_values.push((json!(ratio), format!("{:.2}", ratio)));
+                    }
+                    ColumnField::DoneDate => {
+                        let done_date = torrent.done_date.unwrap();
+                        row_values.push((json!(done_date), date(done_date)));
+                    }
+                    ColumnField::AddedDate => {
+                        let added_date = torrent.added_date.unwrap();
+                        row_values.push((json!(added_date), date(added_date)));
+                    }
+                    ColumnField::Daemon => {
+                        let daemon = self.daemon_name(self.torrent_owners[i]).to_string();
+                        row_values.push((json!(daemon), daemon));
+                    }
+                }
+            }
+            rows.push(row_values);
+        }
+        let mut header_rows = Vec::new();
+        for field in &self.all_info_columns {
+            if !field.show {
+                continue;
+            }
+            header_rows.push(field.column.as_str());
+        }
+
+        (header_rows, rows)
+    }
+
+    pub fn set_columns(&mut self, keys: &[String]) -> Result<(), String> {
+        let mut columns = Vec::new();
+        for key in keys {
+            let index = self
+                .all_info_columns
+                .iter()
+                .position(|field| to_key(&field.column.as_str()) == *key)
+                .ok_or_else(|| format!("unknown column '{}'", key))?;
+            let mut field = self.all_info_columns.remove(index);
+            field.show = true;
+            columns.push(field);
+        }
+
+        for mut field in self.all_info_columns.drain(..) {
+            field.show = false;
+            columns.push(field);
+        }
+        self.all_info_columns = columns;
+        Ok(())
+    }
+
+    pub fn get_session_stats(&self) -> Option<Vec<(&'static str, String)>> {
+        let stats = self
+            .get_session_values()?
+            .into_iter()
+            .map(|(label, _, human)| (label, human))
+            .collect();
+        Some(stats)
+    }
+
+    pub fn get_session_values(&self) -> Option<Vec<(&'static str, Value, String)>> {
+        let session_stats = self.session_stats.as_ref()?;
+        let session = self.session.as_ref()?;
+        let current_stats = &session_stats.current_stats;
+
+        Some(vec![
+            (
+                "Down",
+                json!(session_stats.download_speed),
+                convert_rate(session_stats.download_speed),
+            ),
+            (
+                "Up",
+                json!(session_stats.upload_speed),
+                convert_rate(session_stats.upload_speed),
+            ),
+            (
+                "Downloaded",
+                json!(current_stats.downloaded_bytes),
+                convert_bytes(current_stats.downloaded_bytes),
+            ),
+            (
+                "Uploaded",
+                json!(current_stats.uploaded_bytes),
+                convert_bytes(current_stats.uploaded_bytes),
+            ),
+            (
+                "Slow Mode",
+                json!(session.alt_speed_enabled),
+                session.alt_speed_enabled.to_string(),
+            ),
+            (
+                "Slow Mode Down",
+                json!(session.alt_speed_down * 1000),
+                convert_rate(session.alt_speed_down * 1000),
+            ),
+            (
+                "Slow Mode Up",
+                json!(session.alt_speed_up * 1000),
+                convert_rate(session.alt_speed_up * 1000),
+            ),
+        ])
+    }
+
+    pub fn toggle_show_column(&mut self) {
+        self.all_info_columns[self.selected_column.unwrap()].show =
+            !self.all_info_columns[self.selected_column.unwrap()].show;
+    }
+
+    pub async fn rename_torrent(&mut self) -> RpcResult<()> {
+        self.ensure_selected()?;
+        let client = self.selected_client();
+        let mut client = client.lock().await;
+        check(
+            client
+                .torrent_rename_path(
+                    vec![Id::Id(self.get_selected_torrent_id())],
+                    self.get_selected_torrent_name(),
+                    self.input.to_owned(),
+                )
+                .await?,
+        )?;
+        Ok(())
+    }
+
+    // Renames the node selected in the Files tab, `path` is relative to the
+    // download directory
+    pub async fn rename_file(&mut self) -> RpcResult<()> {
+        self.ensure_selected()?;
+        let path = match self.tree.selected_node() {
+            Some(node) => node.path.to_string_lossy().to_string(),
+            None => return Err("no file selected".into()),
+        };
+        let name = self.input.trim().to_owned();
+
+        let client = self.selected_client();
+        let mut client = client.lock().await;
+        check(
+            client
+                .torrent_rename_path(
+                    vec![Id::Id(self.get_selected_torrent_id())],
+                    path,
+                    name.to_owned(),
+                )
+                .await?,
+        )?;
+        drop(client);
+
+        self.tree.rename_selected(&name);
+        self.refresh_tree();
+        Ok(())
+    }
+
+    pub async fn add_torrent(&mut self) -> RpcResult<()> {
+        self.ensure_connected()?;
+        let filename = match &self.add_source {
+            Some(source) => source.filename(),
+            None => return Err("nothing to add".into()),
+        };
+        let add: TorrentAddArgs = self.add_form.args(filename);
+        add_torrent_to(&self.daemons[0].client, add).await?;
+
+        self.add_form.remember_download_dir();
+        Ok(())
+    }
+
+    pub async fn remove_torrent(&mut self) -> RpcResult<()> {
+        self.ensure_selected()?;
+        for (client, ids) in self.targets() {
+            let mut client = client.lock().await;
+            check(client.torrent_remove(ids, self.delete_files).await?)?;
+        }
+        self.marked.clear();
+        self.visual_anchor = None;
+        Ok(())
+    }
+
+    pub async fn verify_torrent(&mut self) -> RpcResult<()> {
+        self.torrent_action(TorrentAction::Verify).await
+    }
+
+    fn ensure_connected(&self) -> RpcResult<()> {
+        if !self.connection_state.is_connected() {
+            return Err("not connected to the daemon".into());
+        }
+        Ok(())
+    }
+
+    fn ensure_selected(&self) -> RpcResult<()> {
+        self.ensure_connected()?;
+        if self.visible_torrents.is_empty() {
+            return Err("no torrent selected".into());
+        }
+        Ok(())
+    }
+
+    // Enters the selected directory, or picks the selected file
+    pub fn browser_enter(&mut self) {
+        let path = match self.browser.enter() {
+            Some(path) => path,
+            None => return,
+        };
+
+        match self.floating_widget {
+            FloatingWidget::CreateSource => self.pick_create_source(path),
+            _ => {
+                self.add_source = Some(AddSource::File(path));
+                self.open_add_confirm();
+            }
+        }
+    }
+
+    pub fn browsing(&self) -> bool {
+        matches!(
+            self.floating_widget,
+            FloatingWidget::AddTorrent | FloatingWidget::CreateSource
+        )
+    }
+
+    pub fn go_to_bookmark(&mut self, index: usize) {
+        if let Some((_, dir)) = self.config.bookmarks.get(index) {
+            self.browser.go_to(dir.to_owned());
+        }
+    }
+
+    // Starts with the clipboard contents when they look like a link
+    pub fn open_add_link(&mut self) {
+        self.input.clear();
+        self.start_clipboard_read(Paste::Prefill);
+
+        self.add_error = None;
+        self.floating_widget = FloatingWidget::AddLink;
+        self.input_mode = InputMode::Editing;
+    }
+
+    pub fn paste_clipboard(&mut self) {
+        self.start_clipboard_read(Paste::Append);
+    }
+
+    // The clipboard tool runs in a task of its own, so a slow one doesn't
+    // hold up the UI
+    fn start_clipboard_read(&mut self, paste: Paste) {
+        let (sender, receiver) = oneshot::channel();
+        let command = self.config.clipboard_command.to_owned();
+        tokio::spawn(async move {
+            let _ = sender.send(read_clipboard(command).await);
+        });
+        self.clipboard_read = Some((paste, receiver));
+    }
+
+    // Checked on every tick, the text is dropped if the prompt was closed
+    pub fn finish_clipboard_read(&mut self) {
+        let text = match &mut self.clipboard_read {
+            Some((_, receiver)) => match receiver.try_recv() {
+                Ok(text) => text,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Closed) => None,
+            },
+            None => return,
+        };
+        let (paste, _) = self.clipboard_read.take().unwrap();
+
+        let text = match text {
+            Some(text) if matches!(self.floating_widget, FloatingWidget::AddLink) => text,
+            _ => return,
+        };
+        match paste {
+            Paste::Prefill => {
+                if self.input.is_empty() && AddSource::from_link(&text).is_ok() {
+                    self.input = text;
+                }
+            }
+            Paste::Append => {
+                self.input.push_str(&text);
+                self.add_error = None;
+            }
+        }
+    }
+
+    pub fn confirm_add_link(&mut self) {
+        match AddSource::from_link(&self.input) {
+            Ok(source) => {
+                self.add_source = Some(source);
+                self.input_mode = InputMode::Normal;
+                self.open_add_confirm();
+            }
+            Err(err) => self.add_error = Some(err),
+        }
+    }
+
+    // Torrents are added to the first daemon, so its session and torrents
+    // provide the directory completions
+    pub fn open_add_confirm(&mut self) {
+        let default_dir = self
+            .session
+            .as_ref()
+            .map(|session| session.download_dir.to_owned());
+        let mut torrents: Vec<&Torrent> = self
+            .torrents
+            .iter()
+            .zip(&self.torrent_owners)
+            .filter(|(_, owner)| **owner == 0)
+            .map(|(torrent, _)| torrent)
+            .collect();
+        torrents.sort_by_key(|torrent| std::cmp::Reverse(torrent.added_date));
+        let torrent_dirs = torrents
+            .into_iter()
+            .filter_map(|torrent| torrent.download_dir.to_owned())
+            .collect();
+
+        self.preview_error = None;
+        let metainfo = match &self.add_source {
+            Some(AddSource::File(path)) => match Metainfo::read_from_file(path) {
+                Ok(torrent) => Some(torrent),
+                Err(_) => {
+                    self.preview_error = Some(preview::read_error(path));
+                    None
+                }
+            },
+            _ => None,
+        };
+        self.add_form
+            .reset(metainfo.as_ref(), default_dir, torrent_dirs);
+        self.add_error = None;
+        self.add_duplicate = self.find_duplicate(metainfo.as_ref());
+        self.add_preview = metainfo.as_ref().map(Preview::new);
+        self.preview_scroll = 0;
+        self.floating_widget = match self.add_duplicate {
+            Some(_) => FloatingWidget::AddDuplicate,
+            None => FloatingWidget::AddTorrentConfirm,
+        };
+    }
+
+    // URLs are fetched by the daemon, so only files and magnet links can be
+    // checked before adding them
+    fn find_duplicate(&self, metainfo: Option<&Metainfo>) -> Option<Duplicate> {
+        let (hash, trackers) = match (&self.add_source, metainfo) {
+            (Some(AddSource::File(_)), Some(metainfo)) => {
+                (metainfo.info_hash(), announce_urls(metainfo))
+            }
+            (Some(AddSource::Magnet(magnet)), _) => {
+                (magnet.info_hash()?, magnet.trackers.to_owned())
+            }
+            _ => return None,
+        };
+
+        let torrent = self
+            .torrents
+            .iter()
+            .zip(&self.torrent_owners)
+            .find(|(torrent, owner)| {
+                **owner == 0
+                    && matches!(&torrent.hash_string, Some(existing) if existing.eq_ignore_ascii_case(&hash))
+            })
+            .map(|(torrent, _)| torrent)?;
+
+        let existing: Vec<&str> = torrent
+            .tracker_stats
+            .iter()
+            .flatten()
+            .map(|stat| stat.announce.as_str())
+            .collect();
+        let mut new_trackers: Vec<String> = Vec::new();
+        for tracker in trackers {
+            if !existing.contains(&tracker.as_str()) && !new_trackers.contains(&tracker) {
+                new_trackers.push(tracker);
+            }
+        }
+
+        Some(Duplicate {
+            id: torrent.id.unwrap(),
+            name: torrent.name.to_owned().unwrap_or_default(),
+            status: torrent.status.unwrap_or_default(),
+            hash,
+            new_trackers,
+        })
+    }
+
+    pub async fn merge_trackers(&mut self) -> RpcResult<()> {
+        self.ensure_connected()?;
+        let duplicate = match &self.add_duplicate {
+            Some(duplicate) => duplicate,
+            None => return Err("no duplicate torrent".into()),
+        };
+        if duplicate.new_trackers.is_empty() {
+            return Ok(());
+        }
+
+        let mut client = self.daemons[0].client.lock().await;
+        let args = TorrentSetArgs {
+            tracker_add: Some(duplicate.new_trackers.to_owned()),
+            ..TorrentSetArgs::default()
+        };
+        check(
+            client
+                .torrent_set(args, Some(vec![Id::Id(duplicate.id)]))
+                .await?,
+        )?;
+        log::info!(
+            "Added {} trackers to {}",
+            duplicate.new_trackers.len(),
+            duplicate.name
+        );
+        self.poll_notify.notify_one();
+        Ok(())
+    }
+
+    // Only one torrent is hashed at a time, opening the dialog again while
+    // it runs shows its progress
+    pub fn open_create_torrent(&mut self) {
+        if self.create_job.is_some() {
+            self.floating_widget = FloatingWidget::CreateProgress;
+            return;
+        }
+
+        self.browser.open(false);
+        self.floating_widget = FloatingWidget::CreateSource;
+    }
+
+    // Space picks the selected directory itself instead of entering it
+    pub fn pick_selected_source(&mut self) {
+        if let Some(entry) = self.browser.selected_entry() {
+            let path = entry.path.to_owned();
+            self.pick_create_source(path);
+        }
+    }
+
+    fn pick_create_source(&mut self, path: PathBuf) {
+        self.create_form = Some(CreateForm::new(path));
+        self.floating_widget = FloatingWidget::CreateTorrent;
+    }
+
+    pub fn select_create_row(&mut self) {
+        let form = match self.create_form.as_mut() {
+            Some(form) => form,
+            None => return,
+        };
+
+        let row = form.row();
+        if row.is_text() {
+            self.input = form.input(row);
+            self.input_mode = InputMode::Editing;
+        } else {
+            form.toggle();
+        }
+    }
+
+    pub fn confirm_create_input(&mut self) {
+        if let Some(form) = self.create_form.as_mut() {
+            match form.set(&self.input) {
+                Ok(()) => {
+                    form.error = None;
+                    self.input_mode = InputMode::Normal;
+                }
+                Err(err) => form.error = Some(err),
+            }
+        }
+    }
+
+    pub fn start_create_torrent(&mut self) {
+        let form = match self.create_form.as_mut() {
+            Some(form) => form,
+            None => return,
+        };
+
+        match form.build() {
+            Ok(build) => {
+                log::info!("Creating {}", form.output);
+                self.create_job = Some(CreateJob::new(form, build));
+                self.floating_widget = FloatingWidget::CreateProgress;
+            }
+            Err(err) => form.error = Some(err),
+        }
+    }
+
+    // Checked on every tick, a hashed torrent is handed to
+    // finish_create_torrent together with the daemon it's added to
+    pub fn take_finished_create(&mut self) -> Option<(CreateJob, SharedClient)> {
+        match &self.create_job {
+            Some(job) if job.build.is_finished() => (),
+            _ => return None,
+        }
+        if let FloatingWidget::CreateProgress = self.floating_widget {
+            self.floating_widget = FloatingWidget::None;
+        }
+        let job = self.create_job.take().unwrap();
+        Some((job, self.daemons[0].client.clone()))
+    }
+
+    // Checks the data in the add dialog's download directory against the
+    // .torrent file, so copied data can be checked before it's added
+    pub fn start_local_verify(&mut self) {
+        if self.verify_job.is_some() {
+            self.floating_widget = FloatingWidget::VerifyProgress;
+            return;
+        }
+
+        let path = match &self.add_source {
+            Some(AddSource::File(path)) => path,
+            _ => return,
+        };
+        let dir = self.add_form.download_dir.trim();
+        if dir.is_empty() {
+            self.add_error = Some("no download directory to verify".to_string());
+            return;
+        }
+        let torrent = match Metainfo::read_from_file(path) {
+            Ok(torrent) => torrent,
+            Err(err) => {
+                self.add_error = Some(err.to_string());
+                return;
+            }
+        };
+
+        log::info!("Verifying {} in {}", torrent.name, dir);
+        self.verify_job = Some(VerifyJob::start(torrent, PathBuf::from(dir)));
+        self.floating_widget = FloatingWidget::VerifyProgress;
+    }
+
+    // Checked on every tick like creating torrents
+    pub fn finish_local_verify(&mut self) {
+        let name = match &self.verify_job {
+            Some(job) if job.is_finished() => job.name.to_owned(),
+            _ => return,
+        };
+
+        let report = match self.verify_job.take().unwrap().report() {
+            Some(report) => report,
+            None => {
+                log::error!("Could not verify {}", name);
+                return;
+            }
+        };
+        if report.bad_pieces.is_empty() {
+            log::info!("All {} pieces of {} match", report.piece_count, name);
+        } else {
+            log::warn!(
+                "{} of {} pieces of {} don't match",
+                report.bad_pieces.len(),
+                report.piece_count,
+                name
+            );
+        }
+
+        self.verify_report = Some(report);
+        self.selected_verify_file = Some(0);
+        if let FloatingWidget::VerifyProgress = self.floating_widget {
+            self.floating_widget = FloatingWidget::VerifyResults;
+        }
+    }
+
+    pub fn next_verify_file(&mut self) {
+        let files = match &self.verify_report {
+            Some(report) if !report.files.is_empty() => report.files.len(),
+            _ => return,
+        };
+
+        self.selected_verify_file = Some((self.selected_verify_file.unwrap() + 1) % files);
+    }
+
+    pub fn previous_verify_file(&mut self) {
+        let files = match &self.verify_report {
+            Some(report) if !report.files.is_empty() => report.files.len(),
+            _ => return,
+        };
+
+        if self.selected_verify_file > Some(0) {
+            self.selected_verify_file = Some(self.selected_verify_file.unwrap() - 1);
+        } else {
+            self.selected_verify_file = Some(files - 1);
+        }
+    }
+
+    pub fn toggle_preview(&mut self) {
+        match self.floating_widget {
+            FloatingWidget::AddTorrentConfirm if self.add_preview.is_some() => {
+                self.floating_widget = FloatingWidget::AddPreview
+            }
+            FloatingWidget::AddPreview => self.floating_widget = FloatingWidget::AddTorrentConfirm,
+            _ => (),
+        }
+    }
+
+    pub fn scroll_preview_down(&mut self) {
+        let lines = match &self.add_preview {
+            Some(preview) => u16::try_from(preview.lines.len()).unwrap_or(u16::MAX),
+            None => return,
+        };
+        if self.preview_scroll + 1 < lines {
+            self.preview_scroll += 1;
+        }
+    }
+
+    pub fn scroll_preview_up(&mut self) {
+        self.preview_scroll = self.preview_scroll.saturating_sub(1);
+    }
+
+    pub fn toggle_add_torrent_paused(&mut self) {
+        self.add_form.paused = !self.add_form.paused;
+    }
+
+    // Text fields are edited in the input line, the rest change in place
+    pub fn select_add_row(&mut self) {
+        match self.add_form.row() {
+            AddRow::DownloadDir => {
+                self.input = self.add_form.download_dir.to_owned();
+                self.input_mode = InputMode::Editing;
+            }
+            AddRow::PeerLimit => {
+                self.input = self
+                    .add_form
+                    .peer_limit
+                    .map(|limit| limit.to_string())
+                    .unwrap_or_default();
+                self.input_mode = InputMode::Editing;
+            }
+            AddRow::Priority => self.add_form.cycle_priority(),
+            AddRow::Paused => self.toggle_add_torrent_paused(),
+            AddRow::File(index) => self.add_form.toggle_file(index),
+        }
+    }
+
+    // An empty peer limit leaves the daemon's default in place
+    pub fn confirm_add_input(&mut self) {
+        match self.add_form.row() {
+            AddRow::DownloadDir => self.add_form.download_dir = self.input.trim().to_string(),
+            AddRow::PeerLimit => match self.input.trim() {
+                "" => self.add_form.peer_limit = None,
+                limit => match limit.parse::<i64>() {
+                    Ok(limit) if limit > 0 => self.add_form.peer_limit = Some(limit),
+                    _ => {
+                        self.add_error = Some(format!("invalid peer limit '{}'", limit));
+                        return;
+                    }
+                },
+            },
+            _ => (),
+        }
+
+        self.add_error = None;
+        self.input_mode = InputMode::Normal;
+    }
+
+    pub fn complete_download_dir(&mut self) {
+        if let AddRow::DownloadDir = self.add_form.row() {
+            if let Some(dir) = self.add_form.complete(&self.input) {
+                self.input = dir;
+            }
+        }
+    }
+
+    fn selected_client(&self) -> SharedClient {
+        let owner = self.torrent_owners[self.selected_torrent.unwrap()];
+        self.daemons[owner].client.clone()
+    }
+
+    fn get_selected_torrent_id(&self) -> i64 {
+        self.torrents[self.selected_torrent.unwrap()].id.unwrap()
+    }
+
+    pub fn get_selected_torrent(&self) -> &Torrent {
+        &self.torrents[self.selected_torrent.unwrap()]
+    }
+
+    pub fn get_selected_torrent_name(&self) -> String {
+        self.torrents[self.selected_torrent.unwrap()]
+            .name
+            .to_owned()
+            .unwrap()
+    }
+}
+
+fn check<T: RpcResponseArgument>(response: RpcResponse<T>) -> RpcResult<T> {
+    if response.result != "success" {
+        return Err(response.result.into());
+    }
+    Ok(response.arguments)
+}
+
+// Shared by the add dialog, the command line and the watch directories
+// Writes the torrent and adds it with the directory it was created from as
+// download directory, without holding the App lock
+pub async fn finish_create_torrent(job: CreateJob, client: SharedClient) {
+    let CreateJob {
+        build,
+        output,
+        seed_dir,
+    } = job;
+
+    // Something else may have written it while the data was hashed
+    if output.exists() {
+        log::error!(
+            "Could not create torrent: {} already exists",
+            output.display()
+        );
+        return;
+    }
+    let result = build
+        .get_output()
+        .and_then(|torrent| torrent.write_into_file(&output));
+    if let Err(err) = result {
+        log::error!("Could not create torrent: {}", err);
+        return;
+    }
+    log::info!("Created {}", output.display());
+
+    if let Some(dir) = seed_dir {
+        let add = TorrentAddArgs {
+            filename: Some(output.to_string_lossy().to_string()),
+            download_dir: Some(dir.to_string_lossy().to_string()),
+            ..TorrentAddArgs::default()
+        };
+        match add_torrent_to(&client, add).await {
+            Ok(()) => log::info!("Added {} for seeding", output.display()),
+            Err(err) => log::error!("Could not add created torrent: {}", err),
+        }
+    }
+}
+
+pub async fn add_torrent_to(client: &SharedClient, add: TorrentAddArgs) -> RpcResult<()> {
+    let mut client = client.lock().await;
+    check(client.torrent_add(add).await?)?;
+    Ok(())
+}
+
+pub type FetchResult = RpcResult<(Vec<Torrent>, SessionStats, SessionGet)>;
+
+async fn fetch_torrents(client: &SharedClient) -> FetchResult {
+    let mut client = client.lock().await;
+    let torrents = check(client.torrent_get(None, None).await?)?.torrents;
+    let session_stats = check(client.session_stats().await?)?;
+    let session = check(client.session_get().await?)?;
+    Ok((torrents, session_stats, session))
+}
+
+pub async fn get_all_torrents<'a>(app: &Arc<Mutex<App<'a>>>) {
+    let clients = app.lock().unwrap().clients();
+    let results = fetch_all_torrents(&clients).await;
+
+    let mut app = app.lock().unwrap();
+
+    // The profile was switched while the requests were in flight
+    if app.daemons.len() != clients.len()
+        || !app
+            .daemons
+            .iter()
+            .zip(&clients)
+            .all(|(daemon, client)| Arc::ptr_eq(&daemon.client, client))
+    {
+        return;
+    }
+
+    if let Err(err) = update_torrents(&mut app, results) {
+        let state = app.connection_state.after_error(err);
+        log::warn!("{}", state.banner(&app.daemon_label()).unwrap());
+        app.connection_state = state;
+    }
+}
+
+pub async fn fetch_all_torrents(clients: &[SharedClient]) -> Vec<FetchResult> {
+    let handles: Vec<_> = clients
+        .iter()
+        .cloned()
+        .map(|client| tokio::spawn(async move { fetch_torrents(&client).await }))
+        .collect();
+    let mut results = Vec::new();
+    for handle in handles {
+        results.push(match handle.await {
+            Ok(result) => result,
+            Err(err) => Err(err.into()),
+        });
+    }
+    results
+}
+
+// Fails with the errors of all daemons when none of them could be reached
+pub fn update_torrents(app: &mut App<'_>, results: Vec<FetchResult>) -> Result<(), String> {
+    let mut entries = Vec::new();
+    let mut session_stats: Option<SessionStats> = None;
+    let mut session: Option<SessionGet> = None;
+    let mut errors = Vec::new();
+    let mut new_errors = Vec::new();
+
+    for (owner, result) in results.into_iter().enumerate() {
+        match result {
+            Ok((torrents, daemon_stats, daemon_session)) => {
+                if app.daemons[owner].last_error.take().is_some() && app.aggregated {
+                    log::info!("Reconnected to {}", app.daemon_name(owner));
+                }
+                entries.extend(torrents.into_iter().map(|torrent| (owner, torrent)));
+                if let Some(stats) = session_stats.as_mut() {
+                    stats.download_speed += daemon_stats.download_speed;
+                    stats.upload_speed += daemon_stats.upload_speed;
+                    stats.current_stats.downloaded_bytes +=
+                        daemon_stats.current_stats.downloaded_bytes;
+                    stats.current_stats.uploaded_bytes += daemon_stats.current_stats.uploaded_bytes;
+                } else {
+                    session_stats = Some(daemon_stats);
+                }
+                if session.is_none() {
+                    session = Some(daemon_session);
+                }
+            }
+            Err(err) => {
+                if app.aggregated {
+                    let error = format!("{}: {}", app.daemon_name(owner), err);
+                    if app.daemons[owner].last_error.is_none() {
+                        new_errors.push(error.to_owned());
+                    }
+                    errors.push(error);
+                } else {
+                    errors.push(err.to_string());
+                }
+                app.daemons[owner].last_error = Some(err.to_string());
+            }
+        }
+    }
+
+    let (session_stats, session) = match (session_stats, session) {
+        (Some(session_stats), Some(session)) => (session_stats, session),
+        _ => return Err(errors.join(", ")),
+    };
+    // Unless all of them failed, which the caller reports
+    for error in new_errors {
+        log::warn!("{}", error);
+    }
+
+    if !app.connection_state.is_connected() {
+        log::info!("Connected to {}", app.daemon_label());
+        app.connection_state = ConnectionState::Connected;
+    }
+
+    entries.sort_by(|(a_owner, a), (b_owner, b)| match app.sort_column {
+        ColumnField::Id => compare_int(a.id.unwrap(), b.id.unwrap()),
         ColumnField::Name => compare_string(a.name.as_ref().unwrap(), b.name.as_ref().unwrap()),
         ColumnField::Status => compare_int(a.status.unwrap(), b.status.unwrap()),
         ColumnField::Progress => compare_float(a.percent_done.unwrap(), b.percent_done.unwrap()),
@@ -556,12 +2346,36 @@ pub async fn get_all_torrents<'a>(app: &Arc<Mutex<App<'a>>>) {
         ColumnField::UploadRatio => compare_float(a.upload_ratio.unwrap(), b.upload_ratio.unwrap()),
         ColumnField::DoneDate => compare_int(a.done_date.unwrap(), b.done_date.unwrap()),
         ColumnField::AddedDate => compare_int(a.added_date.unwrap(), b.added_date.unwrap()),
+        ColumnField::Daemon => app.daemon_name(*a_owner).cmp(app.daemon_name(*b_owner)),
     });
 
     if !app.sort_descending {
-        torrents.reverse();
+        entries.reverse();
     }
+    let selected = app.selected_key();
+    let (owners, torrents) = entries.into_iter().unzip();
     app.torrents = torrents;
+    app.torrent_owners = owners;
+    let keys: HashSet<(usize, i64)> = (0..app.torrents.len())
+        .map(|index| app.torrent_key(index))
+        .collect();
+    app.marked.retain(|key| keys.contains(key));
+    if let Some(anchor) = app.visual_anchor {
+        if !keys.contains(&anchor) {
+            app.visual_anchor = None;
+        }
+    }
+    if app.selected_torrent >= Some(app.torrents.len()) {
+        app.selected_torrent = Some(app.torrents.len().saturating_sub(1));
+    }
+    app.select_key(selected);
+    app.apply_filter();
+    if app.selected_key() != selected {
+        app.build_file_tree();
+    } else {
+        app.refresh_tree();
+    }
     app.session_stats = Some(session_stats);
     app.session = Some(session);
+    Ok(())
 }
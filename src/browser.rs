@@ -0,0 +1,206 @@
+use std::{ffi::OsStr, fs, path::PathBuf};
+
+pub struct Entry {
+    pub name: String,
+    pub path: PathBuf,
+    pub is_dir: bool,
+}
+
+// Lists the directories and .torrent files of `dir`, or all files when
+// picking content for a new torrent
+pub struct Browser {
+    pub dir: PathBuf,
+    pub torrents_only: bool,
+    pub entries: Vec<Entry>,
+    pub visible: Vec<usize>,
+    pub selected: Option<usize>,
+    pub show_hidden: bool,
+    pub filter: String,
+    pub error: Option<String>,
+}
+
+impl Browser {
+    pub fn new(dir: PathBuf) -> Browser {
+        Browser {
+            dir,
+            torrents_only: true,
+            entries: Vec::new(),
+            visible: Vec::new(),
+            selected: Some(0),
+            show_hidden: false,
+            filter: String::new(),
+            error: None,
+        }
+    }
+
+    pub fn open(&mut self, torrents_only: bool) {
+        self.torrents_only = torrents_only;
+        self.filter.clear();
+        self.read_dir();
+    }
+
+    pub fn read_dir(&mut self) {
+        self.entries.clear();
+        self.error = None;
+
+        match fs::read_dir(&self.dir) {
+            Ok(paths) => {
+                for path in paths.flatten() {
+                    let path = path.path();
+                    let is_dir = path.is_dir();
+                    if self.torrents_only
+                        && !is_dir
+                        && path.extension().and_then(OsStr::to_str) != Some("torrent")
+                    {
+                        continue;
+                    }
+
+                    self.entries.push(Entry {
+                        name: path.file_name().unwrap().to_string_lossy().to_string(),
+                        path,
+                        is_dir,
+                    });
+                }
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+
+        self.entries.sort_by(|a, b| {
+            b.is_dir
+                .cmp(&a.is_dir)
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+        self.apply_filter();
+    }
+
+    pub fn go_to(&mut self, dir: PathBuf) {
+        self.dir = dir;
+        self.filter.clear();
+        self.read_dir();
+        self.selected = Some(0);
+    }
+
+    pub fn selected_entry(&self) -> Option<&Entry> {
+        Some(&self.entries[*self.visible.get(self.selected?)?])
+    }
+
+    // Enters the selected directory, or returns the selected file
+    pub fn enter(&mut self) -> Option<PathBuf> {
+        let entry = &self.entries[*self.visible.get(self.selected?)?];
+        if entry.is_dir {
+            let dir = entry.path.to_owned();
+            self.go_to(dir);
+            None
+        } else {
+            Some(entry.path.to_owned())
+        }
+    }
+
+    // Goes to the parent directory with the directory we came from selected
+    pub fn up(&mut self) {
+        let parent = match self.dir.parent() {
+            Some(parent) => parent.to_path_buf(),
+            None => return,
+        };
+        let previous = self.dir.to_owned();
+
+        self.go_to(parent);
+        if let Some(index) = self
+            .visible
+            .iter()
+            .position(|index| self.entries[*index].path == previous)
+        {
+            self.selected = Some(index);
+        }
+    }
+
+    pub fn toggle_hidden(&mut self) {
+        self.show_hidden = !self.show_hidden;
+        self.apply_filter();
+    }
+
+    pub fn set_filter(&mut self, filter: &str) {
+        self.filter = filter.to_string();
+        self.apply_filter();
+        self.selected = Some(0);
+    }
+
+    // Best fuzzy matches first, directory order otherwise
+    fn apply_filter(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| self.show_hidden || !entry.name.starts_with('.'))
+            .filter_map(|(index, entry)| {
+                fuzzy_score(&self.filter, &entry.name).map(|score| (score, index))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        self.visible = scored.into_iter().map(|(_, index)| index).collect();
+
+        if self.selected >= Some(self.visible.len()) {
+            self.selected = Some(self.visible.len().saturating_sub(1));
+        }
+    }
+
+    pub fn next(&mut self) {
+        if self.visible.is_empty() {
+            return;
+        }
+
+        self.selected = Some((self.selected.unwrap() + 1) % self.visible.len());
+    }
+
+    pub fn previous(&mut self) {
+        if self.visible.is_empty() {
+            return;
+        }
+
+        if self.selected > Some(0) {
+            self.selected = Some(self.selected.unwrap() - 1);
+        } else {
+            self.selected = Some(self.visible.len() - 1);
+        }
+    }
+}
+
+// The pattern's characters have to appear in order, consecutive characters
+// and a match at the start score higher
+fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut last = None;
+
+    for c in pattern.to_lowercase().chars() {
+        let found = position + text[position..].iter().position(|t| *t == c)?;
+        score += match last {
+            Some(last) if found == last + 1 => 5,
+            _ => 1,
+        };
+        if found == 0 {
+            score += 3;
+        }
+        last = Some(found);
+        position = found + 1;
+    }
+
+    Some(score)
+}
+
+// Expands a leading `~` to the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    let home = match dirs::home_dir() {
+        Some(home) => home,
+        None => return PathBuf::from(path),
+    };
+
+    if path == "~" {
+        home
+    } else if let Some(rest) = path.strip_prefix("~/") {
+        home.join(rest)
+    } else {
+        PathBuf::from(path)
+    }
+}
@@ -0,0 +1,446 @@
+use std::{path::PathBuf, str::FromStr};
+
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand, ValueEnum};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde_json::{Map, Value};
+use transmission_rpc::types::TorrentAction;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    app::{fetch_all_torrents, update_torrents, AddSource, App, RpcResult},
+    config::Config,
+    conversion::to_key,
+};
+
+/// A TUI frontend for Transmission
+#[derive(Parser)]
+#[command(name = "spoiler", version)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Connection profile from config.toml to start with
+    #[arg(long, env = "SPOILER_PROFILE")]
+    pub profile: Option<String>,
+
+    /// Show the torrents of all profiles listed in `aggregate` in one list
+    #[arg(long)]
+    pub aggregate: bool,
+
+    /// Transmission RPC scheme, http or https
+    #[arg(long, env = "SPOILER_SCHEME")]
+    pub scheme: Option<String>,
+
+    /// Transmission RPC host
+    #[arg(long, env = "SPOILER_HOST")]
+    pub host: Option<String>,
+
+    /// Transmission RPC port
+    #[arg(long, env = "SPOILER_PORT")]
+    pub port: Option<u16>,
+
+    /// Transmission RPC path
+    #[arg(long, env = "SPOILER_RPC_PATH")]
+    pub path: Option<String>,
+
+    /// Transmission RPC username
+    #[arg(long, env = "SPOILER_USERNAME")]
+    pub username: Option<String>,
+
+    /// Transmission RPC password, better set in the environment than on the command line
+    #[arg(long, env = "SPOILER_PASSWORD", hide_env_values = true)]
+    pub password: Option<String>,
+
+    /// Command whose output is used as the Transmission RPC password
+    #[arg(long, env = "SPOILER_PASSWORD_COMMAND")]
+    pub password_command: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// List all torrents
+    List {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+        /// Comma-separated columns to show, e.g. name,status,down_speed
+        #[arg(long, value_delimiter = ',')]
+        columns: Option<Vec<String>>,
+        /// Only list torrents matching a filter expression, e.g. "status:seeding ratio<1"
+        #[arg(long)]
+        filter: Option<String>,
+    },
+    /// Add a torrent from a .torrent file, a magnet link or a URL
+    Add {
+        /// Path to a .torrent file, a magnet link or an http(s) URL
+        source: String,
+        /// Add the torrent paused
+        #[arg(long)]
+        paused: bool,
+    },
+    /// Pause torrents
+    Pause {
+        #[arg(required = true)]
+        ids: Vec<TorrentId>,
+    },
+    /// Resume paused torrents
+    Resume {
+        #[arg(required = true)]
+        ids: Vec<TorrentId>,
+    },
+    /// Verify the local data of torrents
+    Verify {
+        #[arg(required = true)]
+        ids: Vec<TorrentId>,
+    },
+    /// Rename a torrent
+    Rename { id: TorrentId, name: String },
+    /// Remove torrents
+    Remove {
+        #[arg(required = true)]
+        ids: Vec<TorrentId>,
+        /// Also delete the downloaded files
+        #[arg(long)]
+        delete_data: bool,
+    },
+    /// Show session statistics
+    Stats {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = Format::Table)]
+        format: Format,
+    },
+}
+
+// A torrent id, `profile:id` picks the daemon in the aggregated view
+#[derive(Clone)]
+pub struct TorrentId {
+    profile: Option<String>,
+    id: i64,
+}
+
+impl FromStr for TorrentId {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (profile, id) = match text.rsplit_once(':') {
+            Some((profile, id)) => (Some(profile.to_string()), id),
+            None => (None, text),
+        };
+        match id.parse() {
+            Ok(id) => Ok(TorrentId { profile, id }),
+            Err(_) => Err(format!("invalid torrent id '{}'", text)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+impl Args {
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(name) = &self.profile {
+            match config.profile_index(name) {
+                Some(index) => config.default_profile = index,
+                None => Args::command()
+                    .error(
+                        ErrorKind::InvalidValue,
+                        format!("unknown profile '{}'", name),
+                    )
+                    .exit(),
+            }
+        }
+
+        if self.aggregate {
+            config.start_aggregated = true;
+        }
+
+        let connection = &mut config.profiles[config.default_profile].connection;
+        if let Some(scheme) = &self.scheme {
+            connection.scheme = scheme.to_owned();
+        }
+        if let Some(host) = &self.host {
+            connection.host = host.to_owned();
+        }
+        if let Some(port) = self.port {
+            connection.port = port;
+        }
+        if let Some(path) = &self.path {
+            connection.path = path.to_owned();
+        }
+        if let Some(username) = &self.username {
+            connection.username = Some(username.to_owned());
+        }
+        if let Some(password) = &self.password {
+            connection.password = Some(password.to_owned());
+            connection.password_command = None;
+        }
+        if let Some(command) = &self.password_command {
+            connection.password_command = Some(command.to_owned());
+        }
+    }
+}
+
+// Subcommands have no log pane, so warnings are written to stderr instead
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let level = match record.level() {
+                Level::Error => "error",
+                _ => "warning",
+            };
+            eprintln!("spoiler: {}: {}", level, record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+pub fn init_logger() {
+    static LOGGER: StderrLogger = StderrLogger;
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(LevelFilter::Warn);
+}
+
+pub async fn run(command: Command, mut app: App<'static>) -> i32 {
+    let results = fetch_all_torrents(&app.clients()).await;
+    if let Err(err) = update_torrents(&mut app, results) {
+        eprintln!("spoiler: {}", err);
+        return 1;
+    }
+
+    match run_command(command, &mut app).await {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("spoiler: {}", err);
+            1
+        }
+    }
+}
+
+async fn run_command(command: Command, app: &mut App<'static>) -> RpcResult<()> {
+    match command {
+        Command::List {
+            format,
+            columns,
+            filter,
+        } => {
+            if let Some(columns) = columns {
+                app.set_columns(&columns)?;
+            }
+            if let Some(filter) = filter {
+                app.set_filter(&filter);
+                if let Some(err) = app.filter_error.take() {
+                    return Err(err.into());
+                }
+            }
+
+            let (header, rows) = app.get_torrent_values();
+            let text: Vec<bool> = app
+                .all_info_columns
+                .iter()
+                .filter(|field| field.show)
+                .map(|field| field.column.is_text())
+                .collect();
+            match format {
+                Format::Table => print_table(
+                    header,
+                    rows.into_iter()
+                        .map(|row| row.into_iter().map(|(_, human)| human).collect())
+                        .collect(),
+                ),
+                Format::Json => {
+                    let torrents: Vec<Value> = rows
+                        .into_iter()
+                        .map(|row| Value::Object(to_json_object(&header, &text, row)))
+                        .collect();
+                    println!("{}", serde_json::to_string_pretty(&torrents)?);
+                }
+                Format::Csv => print_csv(&header, &text, rows),
+            }
+        }
+        Command::Stats { format } => {
+            let stats = app.get_session_values().unwrap_or_default();
+            match format {
+                Format::Table => print_table(
+                    Vec::new(),
+                    stats
+                        .into_iter()
+                        .map(|(label, _, human)| vec![format!("{}:", label), human])
+                        .collect(),
+                ),
+                Format::Json => {
+                    let (header, text, row) = split_session_values(stats);
+                    let stats = Value::Object(to_json_object(&header, &text, row));
+                    println!("{}", serde_json::to_string_pretty(&stats)?);
+                }
+                Format::Csv => {
+                    let (header, text, row) = split_session_values(stats);
+                    print_csv(&header, &text, vec![row]);
+                }
+            }
+        }
+        Command::Add { source, paused } => {
+            let path = PathBuf::from(&source);
+            app.add_source = Some(if path.exists() {
+                AddSource::File(path.canonicalize()?)
+            } else {
+                AddSource::from_link(&source)?
+            });
+            app.add_form.paused = paused;
+            app.add_torrent().await?;
+        }
+        Command::Pause { ids } => {
+            mark_torrents(app, ids)?;
+            app.torrent_action(TorrentAction::Stop).await?;
+        }
+        Command::Resume { ids } => {
+            mark_torrents(app, ids)?;
+            app.torrent_action(TorrentAction::Start).await?;
+        }
+        Command::Verify { ids } => {
+            mark_torrents(app, ids)?;
+            app.verify_torrent().await?;
+        }
+        Command::Rename { id, name } => {
+            select_torrent(app, &id)?;
+            app.input = name;
+            app.rename_torrent().await?;
+        }
+        Command::Remove { ids, delete_data } => {
+            mark_torrents(app, ids)?;
+            app.delete_files = delete_data;
+            app.remove_torrent().await?;
+        }
+    }
+
+    Ok(())
+}
+
+fn select_torrent(app: &mut App<'static>, id: &TorrentId) -> RpcResult<()> {
+    app.select_torrent_by_id(id.profile.as_deref(), id.id)?;
+    Ok(())
+}
+
+fn mark_torrents(app: &mut App<'static>, ids: Vec<TorrentId>) -> RpcResult<()> {
+    for id in ids {
+        select_torrent(app, &id)?;
+        let owner = app.torrent_owners[app.selected_torrent.unwrap()];
+        app.marked.insert((owner, id.id));
+    }
+    Ok(())
+}
+
+// Unlike torrent columns every session value is always there, so the text
+// flags come from the values themselves
+fn split_session_values(
+    stats: Vec<(&'static str, Value, String)>,
+) -> (Vec<String>, Vec<bool>, Vec<(Value, String)>) {
+    let mut header = Vec::new();
+    let mut text = Vec::new();
+    let mut row = Vec::new();
+    for (label, raw, human) in stats {
+        header.push(label.to_string());
+        text.push(raw.is_string());
+        row.push((raw, human));
+    }
+    (header, text, row)
+}
+
+// Numeric values are written as is, with the formatted string in a separate
+// `<key>_human` field next to them. `text` marks the fields without one, the
+// same flags go to json_keys so the CSV header always matches the rows.
+fn to_json_object(
+    header: &[String],
+    text: &[bool],
+    row: Vec<(Value, String)>,
+) -> Map<String, Value> {
+    let mut object = Map::new();
+    for ((label, text), (raw, human)) in header.iter().zip(text).zip(row) {
+        let key = to_key(label);
+        object.insert(key.to_owned(), raw);
+        if !text {
+            object.insert(format!("{}_human", key), Value::String(human));
+        }
+    }
+    object
+}
+
+fn json_keys(header: &[String], text: &[bool]) -> Vec<String> {
+    let mut keys = Vec::new();
+    for (label, text) in header.iter().zip(text) {
+        let key = to_key(label);
+        keys.push(key.to_owned());
+        if !text {
+            keys.push(format!("{}_human", key));
+        }
+    }
+    keys
+}
+
+// The header is printed even without rows, so scripts can rely on it
+fn print_csv(header: &[String], text: &[bool], rows: Vec<Vec<(Value, String)>>) {
+    let keys: Vec<String> = json_keys(header, text)
+        .iter()
+        .map(|key| csv_field(key))
+        .collect();
+    println!("{}", keys.join(","));
+
+    for row in rows {
+        let values: Vec<String> = to_json_object(header, text, row)
+            .values()
+            .map(|value| match value {
+                Value::String(value) => csv_field(value),
+                value => value.to_string(),
+            })
+            .collect();
+        println!("{}", values.join(","));
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn print_table(header: Vec<String>, rows: Vec<Vec<String>>) {
+    let mut widths: Vec<usize> = header.iter().map(|cell| cell.width()).collect();
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            if i >= widths.len() {
+                widths.push(0);
+            }
+            widths[i] = widths[i].max(cell.width());
+        }
+    }
+
+    let print_row = |row: &[String]| {
+        let mut line = String::new();
+        for (i, cell) in row.iter().enumerate() {
+            line.push_str(cell);
+            if i + 1 < row.len() {
+                line.push_str(&" ".repeat(widths[i] - cell.width() + 2));
+            }
+        }
+        println!("{}", line);
+    };
+
+    if !header.is_empty() {
+        print_row(&header);
+    }
+    for row in &rows {
+        print_row(row);
+    }
+}
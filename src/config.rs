@@ -1,5 +1,143 @@
 use colors_transform::Rgb;
-use std::{collections::HashMap, path::PathBuf, str::FromStr};
+use std::{collections::HashMap, fmt, path::PathBuf, process::Command, str::FromStr};
+use transmission_rpc::{types::BasicAuth, TransClient};
+
+use crate::{browser::expand_home, netrc};
+
+pub struct Connection {
+    pub scheme: String,
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub password_command: Option<String>,
+    pub netrc: Option<PathBuf>,
+}
+
+impl Connection {
+    pub fn url(&self) -> String {
+        let path = self.path.trim_start_matches('/');
+        format!("{}://{}:{}/{}", self.scheme, self.host, self.port, path)
+    }
+
+    fn set(&mut self, key: &str, value: String) {
+        match key {
+            "scheme" => self.scheme = value,
+            "host" => self.host = value,
+            "port" => {
+                if let Ok(port) = value.parse() {
+                    self.port = port;
+                }
+            }
+            "path" => self.path = value,
+            "username" => self.username = Some(value),
+            "password" => self.password = Some(value),
+            "password_command" => self.password_command = Some(value),
+            "netrc" => self.netrc = PathBuf::from_str(&value).ok(),
+            _ => (),
+        }
+    }
+
+    pub fn client(&self) -> TransClient {
+        match self.credentials() {
+            Some(auth) => TransClient::with_auth(&self.url(), auth),
+            None => TransClient::new(&self.url()),
+        }
+    }
+
+    fn credentials(&self) -> Option<BasicAuth> {
+        let mut user = self.username.to_owned();
+        let mut password = None;
+
+        if let Some(command) = &self.password_command {
+            password = run_password_command(command);
+        } else if self.password.is_some() {
+            password = self.password.to_owned();
+        } else if let Some(path) = &self.netrc {
+            if let Some(entry) = netrc::lookup(path, &self.host) {
+                if user.is_none() || user == entry.login {
+                    user = entry.login;
+                    password = entry.password;
+                }
+            }
+        }
+
+        if user.is_none() && password.is_none() {
+            return None;
+        }
+
+        Some(BasicAuth {
+            user: user.unwrap_or_default(),
+            password: password.unwrap_or_default(),
+        })
+    }
+}
+
+impl fmt::Debug for Connection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Connection")
+            .field("url", &self.url())
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<hidden>"))
+            .field("password_command", &self.password_command)
+            .finish()
+    }
+}
+
+fn run_password_command(command: &str) -> Option<String> {
+    let output = match Command::new("sh").arg("-c").arg(command).output() {
+        Ok(output) => output,
+        Err(err) => {
+            log::error!("Could not run password command: {}", err);
+            return None;
+        }
+    };
+
+    if !output.status.success() {
+        log::error!("Password command exited with {}", output.status);
+        return None;
+    }
+
+    let password = String::from_utf8_lossy(&output.stdout);
+    Some(password.trim_end_matches(&['\r', '\n'][..]).to_string())
+}
+
+impl Default for Connection {
+    fn default() -> Self {
+        Connection {
+            scheme: "http".to_string(),
+            host: "localhost".to_string(),
+            port: 9091,
+            path: "/transmission/rpc".to_string(),
+            username: None,
+            password: None,
+            password_command: None,
+            netrc: dirs::home_dir().map(|home| home.join(".netrc")),
+        }
+    }
+}
+
+pub struct Profile {
+    pub name: String,
+    pub connection: Connection,
+}
+
+// What happens to a .torrent file once it was added from a watch directory
+pub enum AfterAdd {
+    Rename,
+    Move(PathBuf),
+    Delete,
+}
+
+pub struct WatchDir {
+    pub name: String,
+    pub dir: PathBuf,
+    pub paused: bool,
+    pub download_dir: Option<String>,
+    pub labels: Vec<String>,
+    pub after: AfterAdd,
+}
 
 pub struct Config {
     pub fg_normal: tui::style::Color,
@@ -10,6 +148,41 @@ pub struct Config {
     pub fg_column_hide: tui::style::Color,
     pub bg_column_hide: tui::style::Color,
     pub torrent_search_dir: Option<PathBuf>,
+    pub profiles: Vec<Profile>,
+    pub default_profile: usize,
+    pub aggregate: Vec<String>,
+    pub start_aggregated: bool,
+    pub filters: Vec<(String, String)>,
+    pub show_sidebar: bool,
+    pub show_missing_files: bool,
+    pub clipboard_command: Option<String>,
+    pub bookmarks: Vec<(String, PathBuf)>,
+    pub watch: Vec<WatchDir>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            fg_normal: tui::style::Color::Reset,
+            fg_highlight: get_rgb("#000".to_string()),
+            bg_highlight: get_rgb("#f00".to_string()),
+            fg_column_show: get_rgb("#000".to_string()),
+            bg_column_show: get_rgb("#0f0".to_string()),
+            fg_column_hide: get_rgb("#000".to_string()),
+            bg_column_hide: get_rgb("#00f".to_string()),
+            torrent_search_dir: dirs::download_dir(),
+            profiles: Vec::new(),
+            default_profile: 0,
+            aggregate: Vec::new(),
+            start_aggregated: false,
+            filters: Vec::new(),
+            show_sidebar: true,
+            show_missing_files: false,
+            clipboard_command: None,
+            bookmarks: Vec::new(),
+            watch: Vec::new(),
+        }
+    }
 }
 
 impl Config {
@@ -21,23 +194,57 @@ impl Config {
             .add_source(config::File::from(config_path))
             .build();
 
-        let torrent_search_dir = dirs::download_dir();
+        let mut config = Config::default();
+        let mut watch = None;
 
-        let mut config = Config {
-            fg_normal: tui::style::Color::Reset,
-            fg_highlight: get_rgb("#000".to_string()),
-            bg_highlight: get_rgb("#f00".to_string()),
-            fg_column_show: get_rgb("#000".to_string()),
-            bg_column_show: get_rgb("#0f0".to_string()),
-            fg_column_hide: get_rgb("#000".to_string()),
-            bg_column_hide: get_rgb("#00f".to_string()),
-            torrent_search_dir,
-        };
+        let mut connection = Connection::default();
+        let mut default_profile = None;
 
         if let Ok(conf) = config_build {
-            for field in conf.try_deserialize::<HashMap<String, String>>().unwrap() {
-                let rgb = get_rgb(field.1.to_string());
-                match field.0.as_str() {
+            for (key, value) in conf
+                .try_deserialize::<HashMap<String, config::Value>>()
+                .unwrap()
+            {
+                if key == "profiles" {
+                    config.profiles = get_profiles(value);
+                    continue;
+                }
+
+                if key == "filters" {
+                    config.filters = get_string_table(value);
+                    continue;
+                }
+
+                if key == "bookmarks" {
+                    config.bookmarks = get_string_table(value)
+                        .into_iter()
+                        .map(|(name, dir)| (name, expand_home(&dir)))
+                        .collect();
+                    continue;
+                }
+
+                if key == "watch" {
+                    watch = Some(value);
+                    continue;
+                }
+
+                if key == "aggregate" {
+                    if let Ok(names) = value.into_array() {
+                        config.aggregate = names
+                            .into_iter()
+                            .filter_map(|name| name.into_string().ok())
+                            .collect();
+                    }
+                    continue;
+                }
+
+                let value = match value.into_string() {
+                    Ok(value) => value,
+                    Err(_) => continue,
+                };
+
+                let rgb = get_rgb(value.to_string());
+                match key.as_str() {
                     "fg_normal" => config.fg_normal = rgb,
                     "fg_highlight" => config.fg_highlight = rgb,
                     "bg_highlight" => config.bg_highlight = rgb,
@@ -45,17 +252,70 @@ impl Config {
                     "bg_column_show" => config.bg_column_show = rgb,
                     "fg_column_hide" => config.fg_column_hide = rgb,
                     "bg_column_hide" => config.bg_column_hide = rgb,
-                    "torrent_search_dir" => {
-                        config.torrent_search_dir = PathBuf::from_str(field.1.as_str()).ok()
-                    }
-                    _ => (),
+                    "torrent_search_dir" => config.torrent_search_dir = Some(expand_home(&value)),
+                    "profile" => default_profile = Some(value),
+                    "start_aggregated" => config.start_aggregated = value == "true",
+                    "show_sidebar" => config.show_sidebar = value == "true",
+                    "show_missing_files" => config.show_missing_files = value == "true",
+                    "clipboard_command" => config.clipboard_command = Some(value),
+                    _ => connection.set(&key, value),
                 }
             }
         }
 
+        // Watch directories without `dir` watch torrent_search_dir
+        if let Some(value) = watch {
+            config.watch = get_watch_dirs(value, config.torrent_search_dir.as_ref());
+        }
+
+        // A [profiles.default] table replaces the top-level connection keys
+        match config.profile_index("default") {
+            Some(index) => {
+                log::info!("Using [profiles.default] instead of the top-level connection");
+                let profile = config.profiles.remove(index);
+                config.profiles.insert(0, profile);
+            }
+            None => config.profiles.insert(
+                0,
+                Profile {
+                    name: "default".to_string(),
+                    connection,
+                },
+            ),
+        }
+
+        if let Some(name) = default_profile {
+            if let Some(index) = config.profile_index(&name) {
+                config.default_profile = index;
+            }
+        }
+
         config
     }
 
+    pub fn profile_index(&self, name: &str) -> Option<usize> {
+        self.profiles
+            .iter()
+            .position(|profile| profile.name == name)
+    }
+
+    // Indices of the profiles in `aggregate`, or of every profile if it is empty
+    pub fn aggregate_profiles(&self) -> Vec<usize> {
+        if self.aggregate.is_empty() {
+            return (0..self.profiles.len()).collect();
+        }
+
+        let mut indices = Vec::new();
+        for name in &self.aggregate {
+            match self.profile_index(name) {
+                Some(index) if !indices.contains(&index) => indices.push(index),
+                Some(_) => {}
+                None => log::warn!("aggregate: unknown profile \"{}\"", name),
+            }
+        }
+        indices
+    }
+
     pub fn get_style(&self) -> tui::style::Style {
         tui::style::Style::default().fg(self.fg_normal)
     }
@@ -67,6 +327,123 @@ impl Config {
     }
 }
 
+fn get_profiles(value: config::Value) -> Vec<Profile> {
+    let mut profiles = Vec::new();
+
+    if let Ok(table) = value.into_table() {
+        for (name, value) in table {
+            let mut connection = Connection::default();
+            if let Ok(fields) = value.into_table() {
+                for (key, value) in fields {
+                    if let Ok(value) = value.into_string() {
+                        connection.set(&key, value);
+                    }
+                }
+            }
+            profiles.push(Profile { name, connection });
+        }
+    }
+
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    profiles
+}
+
+fn get_watch_dirs(value: config::Value, default_dir: Option<&PathBuf>) -> Vec<WatchDir> {
+    let mut watch_dirs = Vec::new();
+
+    if let Ok(table) = value.into_table() {
+        for (name, value) in table {
+            let mut dir = default_dir.cloned();
+            let mut paused = false;
+            let mut download_dir = None;
+            let mut labels = Vec::new();
+            let mut after = None;
+            let mut move_to = None;
+
+            if let Ok(fields) = value.into_table() {
+                for (key, value) in fields {
+                    if key == "labels" {
+                        if let Ok(values) = value.into_array() {
+                            labels = values
+                                .into_iter()
+                                .filter_map(|label| label.into_string().ok())
+                                .collect();
+                        }
+                        continue;
+                    }
+
+                    let value = match value.into_string() {
+                        Ok(value) => value,
+                        Err(_) => continue,
+                    };
+                    match key.as_str() {
+                        "dir" => dir = Some(expand_home(&value)),
+                        "paused" => paused = value == "true",
+                        "download_dir" => download_dir = Some(value),
+                        "after" => after = Some(value),
+                        "move_to" => move_to = Some(expand_home(&value)),
+                        _ => (),
+                    }
+                }
+            }
+
+            let after = match (after.as_deref(), move_to) {
+                (None | Some("rename"), _) => AfterAdd::Rename,
+                (Some("delete"), _) => AfterAdd::Delete,
+                (Some("move"), Some(move_to)) => AfterAdd::Move(move_to),
+                (Some("move"), None) => {
+                    log::warn!(
+                        "Watch directory {} has after = \"move\" but no move_to, renaming instead",
+                        name
+                    );
+                    AfterAdd::Rename
+                }
+                (Some(after), _) => {
+                    log::warn!(
+                        "Watch directory {} has unknown after = \"{}\", renaming instead",
+                        name,
+                        after
+                    );
+                    AfterAdd::Rename
+                }
+            };
+            match dir {
+                Some(dir) => watch_dirs.push(WatchDir {
+                    name,
+                    dir,
+                    paused,
+                    download_dir,
+                    labels,
+                    after,
+                }),
+                None => log::warn!(
+                    "Watch directory {} has no dir and there is no torrent_search_dir, ignoring it",
+                    name
+                ),
+            }
+        }
+    }
+
+    watch_dirs.sort_by(|a, b| a.name.cmp(&b.name));
+    watch_dirs
+}
+
+// Name and value pairs of a table of strings, sorted by name
+fn get_string_table(value: config::Value) -> Vec<(String, String)> {
+    let mut entries = Vec::new();
+
+    if let Ok(table) = value.into_table() {
+        for (name, value) in table {
+            if let Ok(value) = value.into_string() {
+                entries.push((name, value));
+            }
+        }
+    }
+
+    entries.sort();
+    entries
+}
+
 pub fn get_rgb(color: String) -> tui::style::Color {
     if let Ok(rgb) = Rgb::from_hex_str(&color) {
         tui::style::Color::Rgb(
@@ -78,3 +455,40 @@ pub fn get_rgb(color: String) -> tui::style::Color {
         tui::style::Color::Reset
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(profiles: &[&str], aggregate: &[&str]) -> Config {
+        Config {
+            profiles: profiles
+                .iter()
+                .map(|name| Profile {
+                    name: name.to_string(),
+                    connection: Connection::default(),
+                })
+                .collect(),
+            aggregate: aggregate.iter().map(|name| name.to_string()).collect(),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn aggregate_empty_means_all_profiles() {
+        let config = config(&["default", "nas", "seedbox"], &[]);
+        assert_eq!(config.aggregate_profiles(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn aggregate_keeps_listed_order() {
+        let config = config(&["default", "nas", "seedbox"], &["seedbox", "default"]);
+        assert_eq!(config.aggregate_profiles(), vec![2, 0]);
+    }
+
+    #[test]
+    fn aggregate_skips_unknown_and_repeated_names() {
+        let config = config(&["default", "nas"], &["nas", "missing", "nas"]);
+        assert_eq!(config.aggregate_profiles(), vec![1]);
+    }
+}
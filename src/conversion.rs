@@ -1,5 +1,6 @@
 use std::cmp::Ordering;
 
+use base64::{engine::general_purpose, Engine};
 use byte_unit::{Byte, ByteUnit};
 use chrono::{DateTime, Duration, NaiveDateTime, Utc};
 use transmission_rpc::types::Torrent;
@@ -69,6 +70,24 @@ pub fn date(date: i64) -> String {
     datetime.format("%d/%m/%Y %H:%M").to_string()
 }
 
+// `pieces` is a base64 encoded bitfield with the first piece in the most
+// significant bit
+pub fn decode_pieces(pieces: &str, count: usize) -> Vec<bool> {
+    let pieces: String = pieces.split_whitespace().collect();
+    let bytes = general_purpose::STANDARD.decode(pieces).unwrap_or_default();
+    (0..count)
+        .map(|i| matches!(bytes.get(i / 8), Some(byte) if byte & (0x80 >> (i % 8)) != 0))
+        .collect()
+}
+
+// Trackers report -1 when the count is unknown
+pub fn count_string(count: i64) -> String {
+    if count < 0 {
+        return String::new();
+    }
+    count.to_string()
+}
+
 pub fn convert_secs(secs: i64) -> String {
     match secs {
         -1 => return String::from("Done"),
@@ -128,12 +147,13 @@ pub fn compare_string(a: &String, b: &String) -> Ordering {
 pub fn get_ratio(up: i64, down: i64) -> f32 {
     let up = up as f32;
     let down = down as f32;
-    let ratio: f32;
     if down == 0.0 {
-        ratio = 0.0;
+        0.0
     } else {
-        ratio = up / down;
+        up / down
     }
+}
 
-    ratio
+pub fn to_key(label: &str) -> String {
+    label.to_lowercase().replace(' ', "_")
 }
@@ -0,0 +1,309 @@
+use std::{
+    path::{Path, PathBuf},
+    thread::{self, JoinHandle},
+};
+
+use lava_torrent::{
+    bencode::BencodeElem,
+    torrent::v1::{TorrentBuild, TorrentBuilder},
+};
+use walkdir::WalkDir;
+
+use crate::conversion::convert_bytes;
+
+const MIN_PIECE_LENGTH: i64 = 16 * 1024;
+const MAX_PIECE_LENGTH: i64 = 16 * 1024 * 1024;
+const TARGET_PIECES: i64 = 2000;
+
+#[derive(Clone, Copy)]
+pub enum CreateRow {
+    Trackers,
+    PieceSize,
+    Private,
+    Comment,
+    Source,
+    Output,
+    Seed,
+}
+
+impl CreateRow {
+    pub const ALL: [CreateRow; 7] = [
+        CreateRow::Trackers,
+        CreateRow::PieceSize,
+        CreateRow::Private,
+        CreateRow::Comment,
+        CreateRow::Source,
+        CreateRow::Output,
+        CreateRow::Seed,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CreateRow::Trackers => "Trackers",
+            CreateRow::PieceSize => "Piece size",
+            CreateRow::Private => "Private",
+            CreateRow::Comment => "Comment",
+            CreateRow::Source => "Source",
+            CreateRow::Output => "Save as",
+            CreateRow::Seed => "Add for seeding",
+        }
+    }
+
+    pub fn is_text(&self) -> bool {
+        !matches!(self, CreateRow::Private | CreateRow::Seed)
+    }
+}
+
+// Settings of a torrent about to be created from `path`
+pub struct CreateForm {
+    pub path: PathBuf,
+    // Not known until the content has been walked in the background
+    pub size: Option<i64>,
+    sizing: Option<JoinHandle<i64>>,
+    pub trackers: String,
+    pub piece_length: Option<i64>,
+    pub private: bool,
+    pub comment: String,
+    pub source: String,
+    pub output: String,
+    pub seed: bool,
+    pub selected: usize,
+    pub error: Option<String>,
+}
+
+impl CreateForm {
+    pub fn new(path: PathBuf) -> CreateForm {
+        let sizing = {
+            let path = path.to_owned();
+            thread::spawn(move || content_size(&path))
+        };
+        let mut output = path.to_owned().into_os_string();
+        output.push(".torrent");
+
+        CreateForm {
+            path,
+            size: None,
+            sizing: Some(sizing),
+            trackers: String::new(),
+            piece_length: None,
+            private: false,
+            comment: String::new(),
+            source: String::new(),
+            output: output.to_string_lossy().to_string(),
+            seed: false,
+            selected: 0,
+            error: None,
+        }
+    }
+
+    // Checked on every tick until the size is known
+    pub fn update_size(&mut self) {
+        match &self.sizing {
+            Some(sizing) if sizing.is_finished() => (),
+            _ => return,
+        }
+        self.size = Some(self.sizing.take().unwrap().join().unwrap_or_default());
+    }
+
+    pub fn row(&self) -> CreateRow {
+        CreateRow::ALL[self.selected]
+    }
+
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % CreateRow::ALL.len();
+    }
+
+    pub fn previous(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+        } else {
+            self.selected = CreateRow::ALL.len() - 1;
+        }
+    }
+
+    pub fn value(&self, row: CreateRow) -> String {
+        match row {
+            CreateRow::Trackers => self.trackers.to_owned(),
+            CreateRow::PieceSize => match self.piece_length {
+                Some(length) => convert_bytes(length),
+                None => match self.length() {
+                    Some(length) => format!("Auto ({})", convert_bytes(length)),
+                    None => "Auto".to_string(),
+                },
+            },
+            CreateRow::Private => self.private.to_string(),
+            CreateRow::Comment => self.comment.to_owned(),
+            CreateRow::Source => self.source.to_owned(),
+            CreateRow::Output => self.output.to_owned(),
+            CreateRow::Seed => self.seed.to_string(),
+        }
+    }
+
+    // What the input line starts with when a text field is edited
+    pub fn input(&self, row: CreateRow) -> String {
+        match row {
+            CreateRow::PieceSize => self
+                .piece_length
+                .map(|length| (length / 1024).to_string())
+                .unwrap_or_default(),
+            row => self.value(row),
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        match self.row() {
+            CreateRow::Private => self.private = !self.private,
+            CreateRow::Seed => self.seed = !self.seed,
+            _ => (),
+        }
+    }
+
+    // Piece sizes are given in KiB, an empty one picks it from the size
+    pub fn set(&mut self, input: &str) -> Result<(), String> {
+        let input = input.trim();
+        match self.row() {
+            CreateRow::Trackers => self.trackers = input.to_string(),
+            CreateRow::PieceSize => {
+                self.piece_length = match input {
+                    "" | "auto" => None,
+                    kib => match kib.parse::<i64>() {
+                        Ok(kib) if is_piece_length(kib.saturating_mul(1024)) => Some(kib * 1024),
+                        _ => {
+                            return Err(format!(
+                                "piece size has to be a power of two between {} and {} KiB",
+                                MIN_PIECE_LENGTH / 1024,
+                                MAX_PIECE_LENGTH / 1024
+                            ))
+                        }
+                    },
+                }
+            }
+            CreateRow::Comment => self.comment = input.to_string(),
+            CreateRow::Source => self.source = input.to_string(),
+            CreateRow::Output => self.output = input.to_string(),
+            CreateRow::Private | CreateRow::Seed => (),
+        }
+        Ok(())
+    }
+
+    pub fn length(&self) -> Option<i64> {
+        self.piece_length
+            .or_else(|| self.size.map(auto_piece_length))
+    }
+
+    // Tiers are separated by spaces, trackers of the same tier by commas
+    fn tiers(&self) -> Vec<Vec<String>> {
+        self.trackers
+            .split_whitespace()
+            .map(|tier| {
+                tier.split(',')
+                    .filter(|tracker| !tracker.is_empty())
+                    .map(|tracker| tracker.to_string())
+                    .collect::<Vec<String>>()
+            })
+            .filter(|tier| !tier.is_empty())
+            .collect()
+    }
+
+    // An existing file isn't overwritten, and seeding needs the directory the
+    // content is in as download directory
+    pub fn build(&self) -> Result<TorrentBuild, String> {
+        let length = match self.length() {
+            Some(length) => length,
+            None => return Err("still counting the size of the content".to_string()),
+        };
+        if Path::new(&self.output).exists() {
+            return Err(format!("{} already exists", self.output));
+        }
+        if self.seed && self.path.parent().is_none() {
+            return Err(format!(
+                "cannot seed {}, it has no parent directory",
+                self.path.display()
+            ));
+        }
+
+        let mut builder = TorrentBuilder::new(&self.path, length)
+            .set_privacy(self.private)
+            .add_extra_field(
+                "created by".to_string(),
+                BencodeElem::String(format!("spoiler {}", env!("CARGO_PKG_VERSION"))),
+            )
+            .add_extra_field(
+                "creation date".to_string(),
+                BencodeElem::Integer(chrono::Utc::now().timestamp()),
+            );
+
+        let tiers = self.tiers();
+        if let Some(announce) = tiers.first().and_then(|tier| tier.first()) {
+            builder = builder.set_announce(Some(announce.to_owned()));
+        }
+        if tiers.len() > 1 || tiers.iter().any(|tier| tier.len() > 1) {
+            builder = builder.set_announce_list(tiers);
+        }
+        if !self.comment.is_empty() {
+            builder = builder.add_extra_field(
+                "comment".to_string(),
+                BencodeElem::String(self.comment.to_owned()),
+            );
+        }
+        if !self.source.is_empty() {
+            builder = builder.add_extra_info_field(
+                "source".to_string(),
+                BencodeElem::String(self.source.to_owned()),
+            );
+        }
+
+        builder.build_non_blocking().map_err(|err| err.to_string())
+    }
+}
+
+// A torrent being hashed by lava_torrent's worker threads
+pub struct CreateJob {
+    pub build: TorrentBuild,
+    pub output: PathBuf,
+    pub seed_dir: Option<PathBuf>,
+}
+
+impl CreateJob {
+    pub fn new(form: &CreateForm, build: TorrentBuild) -> CreateJob {
+        CreateJob {
+            build,
+            output: PathBuf::from(&form.output),
+            seed_dir: if form.seed {
+                form.path.parent().map(Path::to_path_buf)
+            } else {
+                None
+            },
+        }
+    }
+
+    pub fn progress(&self) -> (u64, u64) {
+        (
+            self.build.get_n_piece_processed(),
+            self.build.get_n_piece_total(),
+        )
+    }
+}
+
+fn content_size(path: &Path) -> i64 {
+    WalkDir::new(path)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len() as i64)
+        .sum()
+}
+
+// The smallest power of two that keeps the piece count near TARGET_PIECES
+pub fn auto_piece_length(size: i64) -> i64 {
+    let mut length = MIN_PIECE_LENGTH;
+    while size / length > TARGET_PIECES && length < MAX_PIECE_LENGTH {
+        length *= 2;
+    }
+    length
+}
+
+fn is_piece_length(length: i64) -> bool {
+    (MIN_PIECE_LENGTH..=MAX_PIECE_LENGTH).contains(&length) && length.count_ones() == 1
+}
@@ -0,0 +1,375 @@
+use byte_unit::Byte;
+use chrono::NaiveDate;
+use regex::{Regex, RegexBuilder};
+use transmission_rpc::types::Torrent;
+use tui::{
+    style::{Modifier, Style},
+    text::{Span, Spans},
+};
+
+use crate::conversion::{get_ratio, status_string};
+
+pub struct NameFilter {
+    regex: Regex,
+}
+
+impl NameFilter {
+    // Matching is case-insensitive unless the pattern contains upper case letters
+    pub fn new(pattern: &str, use_regex: bool) -> Result<NameFilter, regex::Error> {
+        let escaped;
+        let pattern = if use_regex {
+            pattern
+        } else {
+            escaped = regex::escape(pattern);
+            &escaped
+        };
+
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(!pattern.chars().any(char::is_uppercase))
+            .build()?;
+        Ok(NameFilter { regex })
+    }
+
+    pub fn is_match(&self, name: &str) -> bool {
+        self.regex.is_match(name)
+    }
+
+    pub fn highlight(&self, name: String) -> Spans<'static> {
+        let style = Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+        let mut spans = Vec::new();
+        let mut last = 0;
+
+        for found in self.regex.find_iter(&name) {
+            if found.start() == found.end() {
+                continue;
+            }
+            spans.push(Span::raw(name[last..found.start()].to_string()));
+            spans.push(Span::styled(found.as_str().to_string(), style));
+            last = found.end();
+        }
+        spans.push(Span::raw(name[last..].to_string()));
+
+        Spans::from(spans)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Field {
+    Size,
+    Ratio,
+    Progress,
+    Added,
+    Done,
+    Down,
+    Up,
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    All,
+    Downloading,
+    Seeding,
+    Stopped,
+    Verifying,
+    Queued,
+    Errored,
+    Active,
+}
+
+impl Category {
+    pub const ALL: [Category; 8] = [
+        Category::All,
+        Category::Downloading,
+        Category::Seeding,
+        Category::Stopped,
+        Category::Verifying,
+        Category::Queued,
+        Category::Errored,
+        Category::Active,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Category::All => "All",
+            Category::Downloading => "Downloading",
+            Category::Seeding => "Seeding",
+            Category::Stopped => "Stopped",
+            Category::Verifying => "Verifying",
+            Category::Queued => "Queued",
+            Category::Errored => "Errored",
+            Category::Active => "Active",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Category> {
+        let name = match name {
+            "paused" => "stopped",
+            "checking" => "verifying",
+            name => name,
+        };
+
+        Category::ALL
+            .into_iter()
+            .find(|category| category.as_str().to_lowercase() == name)
+    }
+
+    pub fn matches(&self, torrent: &Torrent) -> bool {
+        let status = status_string(torrent.status.as_ref().unwrap());
+        match self {
+            Category::All => true,
+            Category::Downloading => status == "Downloading",
+            Category::Seeding => status == "Seeding",
+            Category::Stopped => status == "Stopped",
+            Category::Verifying => status == "Verifying local data",
+            Category::Queued => status.starts_with("Queued"),
+            Category::Errored => torrent.error.unwrap_or(0) != 0,
+            Category::Active => {
+                torrent.rate_download.unwrap_or(0) > 0 || torrent.rate_upload.unwrap_or(0) > 0
+            }
+        }
+    }
+}
+
+enum Term {
+    Status(Category),
+    Tracker(String),
+    Compare(Field, Op, f64),
+}
+
+pub struct Query {
+    name: Option<NameFilter>,
+    terms: Vec<Term>,
+}
+
+impl Query {
+    // What is left once the field expressions are taken out is matched against
+    // the name, so spaces within a regex are kept as they are
+    pub fn parse(pattern: &str, use_regex: bool) -> Result<Query, String> {
+        let mut words = String::new();
+        let mut terms = Vec::new();
+        let mut last = 0;
+
+        for token in pattern.split_whitespace() {
+            if let Some(term) = parse_term(token)? {
+                let start = token.as_ptr() as usize - pattern.as_ptr() as usize;
+                push_words(&mut words, &pattern[last..start]);
+                last = start + token.len();
+                terms.push(term);
+            }
+        }
+        push_words(&mut words, &pattern[last..]);
+
+        let name = if words.is_empty() {
+            None
+        } else {
+            Some(NameFilter::new(&words, use_regex).map_err(|err| err.to_string())?)
+        };
+
+        Ok(Query { name, terms })
+    }
+
+    pub fn matches(&self, torrent: &Torrent) -> bool {
+        if let Some(name) = &self.name {
+            if !name.is_match(torrent.name.as_ref().unwrap()) {
+                return false;
+            }
+        }
+
+        self.terms.iter().all(|term| match term {
+            Term::Status(category) => category.matches(torrent),
+            Term::Tracker(tracker) => torrent
+                .trackers
+                .as_ref()
+                .map(|trackers| {
+                    trackers
+                        .iter()
+                        .any(|t| t.announce.to_lowercase().contains(tracker))
+                })
+                .unwrap_or(false),
+            Term::Compare(field, op, value) => {
+                let actual = field_value(torrent, *field);
+                match op {
+                    Op::Less => actual < *value,
+                    Op::LessOrEqual => actual <= *value,
+                    Op::Greater => actual > *value,
+                    Op::GreaterOrEqual => actual >= *value,
+                    Op::Equal => actual == *value,
+                }
+            }
+        })
+    }
+
+    pub fn highlight(&self, name: String) -> Spans<'static> {
+        match &self.name {
+            Some(filter) => filter.highlight(name),
+            None => Spans::from(name),
+        }
+    }
+}
+
+// The words on either side of a field expression are joined by a single space
+fn push_words(words: &mut String, part: &str) {
+    let part = part.trim();
+    if part.is_empty() {
+        return;
+    }
+    if !words.is_empty() {
+        words.push(' ');
+    }
+    words.push_str(part);
+}
+
+fn parse_term(token: &str) -> Result<Option<Term>, String> {
+    let split = match token.find(&[':', '<', '>', '='][..]) {
+        Some(split) => split,
+        None => return Ok(None),
+    };
+
+    let key = token[..split].to_lowercase();
+    let rest = &token[split..];
+
+    if let Some(value) = rest.strip_prefix(':') {
+        let value = value.to_lowercase();
+        return match key.as_str() {
+            "status" => match Category::from_name(&value) {
+                Some(category) => Ok(Some(Term::Status(category))),
+                None => Err(format!("unknown status '{}'", value)),
+            },
+            "tracker" => Ok(Some(Term::Tracker(value))),
+            // Not a field expression, e.g. a name containing a colon
+            _ => Ok(None),
+        };
+    }
+
+    let field = match key.as_str() {
+        "size" => Field::Size,
+        "ratio" => Field::Ratio,
+        "progress" => Field::Progress,
+        "added" => Field::Added,
+        "done" => Field::Done,
+        "down" => Field::Down,
+        "up" => Field::Up,
+        // Not a field expression, e.g. a regex like [a=b]
+        _ => return Ok(None),
+    };
+
+    let (op, value) = if let Some(value) = rest.strip_prefix("<=") {
+        (Op::LessOrEqual, value)
+    } else if let Some(value) = rest.strip_prefix(">=") {
+        (Op::GreaterOrEqual, value)
+    } else if let Some(value) = rest.strip_prefix('<') {
+        (Op::Less, value)
+    } else if let Some(value) = rest.strip_prefix('>') {
+        (Op::Greater, value)
+    } else {
+        (Op::Equal, rest.trim_start_matches('='))
+    };
+
+    let parsed = match field {
+        Field::Size | Field::Down | Field::Up => Byte::from_str(value)
+            .ok()
+            .map(|bytes| bytes.get_bytes() as f64),
+        Field::Ratio => value.parse().ok(),
+        Field::Progress => value
+            .trim_end_matches('%')
+            .parse::<f64>()
+            .ok()
+            .map(|percent| percent / 100.0),
+        Field::Added | Field::Done => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|date| date.timestamp() as f64),
+    };
+
+    match parsed {
+        Some(parsed) => Ok(Some(Term::Compare(field, op, parsed))),
+        None => Err(format!("invalid value '{}' for {}", value, key)),
+    }
+}
+
+fn field_value(torrent: &Torrent, field: Field) -> f64 {
+    match field {
+        Field::Size => torrent.total_size.unwrap() as f64,
+        Field::Ratio => {
+            get_ratio(torrent.uploaded_ever.unwrap(), torrent.have_valid.unwrap()) as f64
+        }
+        Field::Progress => torrent.percent_done.unwrap() as f64,
+        Field::Added => torrent.added_date.unwrap() as f64,
+        Field::Done => torrent.done_date.unwrap() as f64,
+        Field::Down => torrent.rate_download.unwrap() as f64,
+        Field::Up => torrent.rate_upload.unwrap() as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(query: &Query) -> Option<&str> {
+        query.name.as_ref().map(|name| name.regex.as_str())
+    }
+
+    #[test]
+    fn field_expressions() {
+        let query = Query::parse(
+            "size>1GB status:paused progress<=50% tracker:Example",
+            false,
+        )
+        .unwrap();
+        assert_eq!(name(&query), None);
+        assert_eq!(query.terms.len(), 4);
+        assert!(matches!(
+            query.terms[0],
+            Term::Compare(Field::Size, Op::Greater, size) if size == 1e9
+        ));
+        assert!(matches!(query.terms[1], Term::Status(Category::Stopped)));
+        assert!(matches!(
+            query.terms[2],
+            Term::Compare(Field::Progress, Op::LessOrEqual, progress) if progress == 0.5
+        ));
+        assert!(matches!(&query.terms[3], Term::Tracker(tracker) if tracker == "example"));
+    }
+
+    #[test]
+    fn invalid_values_of_known_fields() {
+        assert!(Query::parse("size>lots", false).is_err());
+        assert!(Query::parse("added=yesterday", false).is_err());
+        assert!(Query::parse("status:bogus", false).is_err());
+    }
+
+    #[test]
+    fn unknown_keys_are_part_of_the_name() {
+        for pattern in ["[a=b]", "x>1", "(?P<n>foo)", "title:subtitle", "a=b"] {
+            let query = Query::parse(pattern, true).unwrap();
+            assert_eq!(name(&query), Some(pattern));
+            assert!(query.terms.is_empty());
+        }
+    }
+
+    #[test]
+    fn name_keeps_spaces() {
+        let query = Query::parse("a  b", true).unwrap();
+        assert_eq!(name(&query), Some("a  b"));
+        assert!(query.name.as_ref().unwrap().is_match("a  b"));
+        assert!(!query.name.as_ref().unwrap().is_match("a b"));
+    }
+
+    #[test]
+    fn name_around_field_expressions() {
+        let query = Query::parse("  foo  size>1GB  bar ratio>=2 ", true).unwrap();
+        assert_eq!(name(&query), Some("foo bar"));
+        assert_eq!(query.terms.len(), 2);
+
+        let query = Query::parse("Some  Name ratio<1", false).unwrap();
+        assert!(query.name.as_ref().unwrap().is_match("Some  Name"));
+    }
+}
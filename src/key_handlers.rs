@@ -1,7 +1,10 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use tui_tree_widget::TreeState;
 
-use crate::app::{App, FloatingWidget, FocusableWidget, InputMode, Route, RouteId};
+use crate::app::{
+    AddSource, App, FilePriority, FloatingWidget, FocusableWidget, InfoTab, InputMode, Route,
+    RouteId,
+};
 
 pub async fn handler(key: KeyEvent, app: &mut App<'static>) {
     match app.input_mode {
@@ -13,25 +16,61 @@ pub async fn handler(key: KeyEvent, app: &mut App<'static>) {
             KeyCode::Char('?') | KeyCode::F(1) => handle_help(app),
             KeyCode::Char('p') => handle_pause(app).await,
             KeyCode::Char('r') => handle_rename(app).await,
+            KeyCode::Char('a') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                handle_mark_all(app)
+            }
             KeyCode::Char('a') => handle_add(app).await,
             KeyCode::Char('q') => app.should_quit = true,
-            KeyCode::Char('d') => handle_remove(app).await,
+            KeyCode::Char('d') => handle_remove(app),
             KeyCode::Char('t') => handle_toggle(app),
             KeyCode::Char('c') => handle_columns(app),
             KeyCode::Char('v') => handle_verify(app).await,
+            KeyCode::Char('P') => handle_profiles(app),
+            KeyCode::Char('/') => handle_filter(app),
+            KeyCode::Char('f') => handle_saved_filters(app),
+            KeyCode::Char('s') => handle_sidebar(app),
+            KeyCode::Char(' ') => handle_mark(app).await,
+            KeyCode::Char('+') => handle_priority(app, FilePriority::High).await,
+            KeyCode::Char('=') => handle_priority(app, FilePriority::Normal).await,
+            KeyCode::Char('-') => handle_priority(app, FilePriority::Low).await,
+            KeyCode::Char('V') => handle_visual(app),
+            KeyCode::Char('u') => handle_reannounce(app).await,
+            KeyCode::Char('m') => handle_add_link(app),
+            KeyCode::Char('i') => app.toggle_preview(),
+            KeyCode::Char('n') => handle_create(app),
+            KeyCode::Char('.') if app.browsing() => app.browser.toggle_hidden(),
+            KeyCode::Char(c @ '1'..='9') if app.browsing() => {
+                app.go_to_bookmark(c as usize - '1' as usize)
+            }
             KeyCode::Enter => handle_enter(app).await,
             KeyCode::Esc => handle_esc(app),
             _ => (),
         },
         InputMode::Editing => match key.code {
-            KeyCode::Enter => {
-                app.rename_torrent().await;
-                app.input_mode = InputMode::Normal;
-                app.floating_widget = FloatingWidget::None
+            KeyCode::Enter => handle_input_confirm(app).await,
+            KeyCode::Char('r')
+                if key.modifiers.contains(KeyModifiers::CONTROL)
+                    && matches!(app.floating_widget, FloatingWidget::Filter) =>
+            {
+                app.toggle_filter_regex()
+            }
+            KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let FloatingWidget::AddLink = app.floating_widget {
+                    app.paste_clipboard();
+                }
+            }
+            KeyCode::Tab => {
+                if let FloatingWidget::AddTorrentConfirm = app.floating_widget {
+                    app.complete_download_dir();
+                }
+            }
+            KeyCode::Char(c) => {
+                app.input.push(c);
+                handle_input_changed(app);
             }
-            KeyCode::Char(c) => app.input.push(c),
             KeyCode::Backspace => {
                 app.input.pop();
+                handle_input_changed(app);
             }
             KeyCode::Esc => handle_esc(app),
             _ => (),
@@ -39,13 +78,107 @@ pub async fn handler(key: KeyEvent, app: &mut App<'static>) {
     }
 }
 
+async fn handle_input_confirm(app: &mut App<'static>) {
+    match app.floating_widget {
+        FloatingWidget::AddLink => {
+            // Stays open with the error shown if the link is invalid
+            app.confirm_add_link();
+            return;
+        }
+        FloatingWidget::AddTorrent | FloatingWidget::CreateSource => {
+            app.input_mode = InputMode::Normal;
+            return;
+        }
+        FloatingWidget::AddTorrentConfirm => {
+            app.confirm_add_input();
+            return;
+        }
+        FloatingWidget::CreateTorrent => {
+            app.confirm_create_input();
+            return;
+        }
+        FloatingWidget::Input => {
+            if let Some(FocusableWidget::FileList) = app.last_route_focused_widget() {
+                if let Err(err) = app.rename_file().await {
+                    log::error!("Could not rename file: {}", err);
+                }
+            } else if let Err(err) = app.rename_torrent().await {
+                log::error!("Could not rename torrent: {}", err);
+            }
+        }
+        FloatingWidget::TrackerInput => {
+            let result = if app.editing_tracker.is_some() {
+                app.replace_tracker().await
+            } else {
+                app.add_tracker().await
+            };
+            if let Err(err) = result {
+                log::error!("Could not update trackers: {}", err);
+            }
+        }
+        _ => (),
+    }
+
+    app.input_mode = InputMode::Normal;
+    app.floating_widget = FloatingWidget::None
+}
+
+fn handle_input_changed(app: &mut App<'static>) {
+    match app.floating_widget {
+        FloatingWidget::Filter => {
+            let pattern = app.input.to_owned();
+            app.set_filter(&pattern);
+        }
+        FloatingWidget::AddLink | FloatingWidget::AddTorrentConfirm => app.add_error = None,
+        FloatingWidget::CreateTorrent => {
+            if let Some(form) = app.create_form.as_mut() {
+                form.error = None;
+            }
+        }
+        FloatingWidget::AddTorrent | FloatingWidget::CreateSource => {
+            let filter = app.input.to_owned();
+            app.browser.set_filter(&filter);
+        }
+        _ => (),
+    }
+}
+
 fn handle_up(key: KeyEvent, app: &mut App<'static>) {
     match app.floating_widget {
-        FloatingWidget::AddTorrent => {
-            app.previous_torrent_file();
+        FloatingWidget::AddTorrent | FloatingWidget::CreateSource => {
+            app.browser.previous();
+            return;
+        }
+        FloatingWidget::Profiles => {
+            app.previous_profile();
+            return;
+        }
+        FloatingWidget::SavedFilters => {
+            app.previous_saved_filter();
+            return;
+        }
+        FloatingWidget::AddTorrentConfirm => {
+            app.add_form.previous();
+            return;
+        }
+        FloatingWidget::AddPreview => {
+            app.scroll_preview_up();
+            return;
+        }
+        FloatingWidget::VerifyResults => {
+            app.previous_verify_file();
+            return;
+        }
+        FloatingWidget::CreateTorrent => {
+            if let Some(form) = app.create_form.as_mut() {
+                form.previous();
+            }
             return;
         }
-        FloatingWidget::AddTorrentConfirm | FloatingWidget::Help => return,
+        FloatingWidget::AddDuplicate
+        | FloatingWidget::CreateProgress
+        | FloatingWidget::VerifyProgress
+        | FloatingWidget::Help => return,
         FloatingWidget::ModifyColumns => {
             if let KeyModifiers::SHIFT = key.modifiers {
                 app.move_column_up();
@@ -61,18 +194,50 @@ fn handle_up(key: KeyEvent, app: &mut App<'static>) {
 
     match app.last_route_focused_widget() {
         Some(FocusableWidget::TorrentList) => app.previous(),
+        Some(FocusableWidget::Sidebar) => app.previous_category(),
         Some(FocusableWidget::FileList) => app.tree.previous_file(),
+        Some(FocusableWidget::PeerList) => app.previous_peer(),
+        Some(FocusableWidget::TrackerList) => app.previous_tracker(),
         _ => (),
     }
 }
 
 fn handle_down(key: KeyEvent, app: &mut App<'static>) {
     match app.floating_widget {
-        FloatingWidget::AddTorrent => {
-            app.next_torrent_file();
+        FloatingWidget::AddTorrent | FloatingWidget::CreateSource => {
+            app.browser.next();
             return;
         }
-        FloatingWidget::AddTorrentConfirm | FloatingWidget::Help => return,
+        FloatingWidget::Profiles => {
+            app.next_profile();
+            return;
+        }
+        FloatingWidget::SavedFilters => {
+            app.next_saved_filter();
+            return;
+        }
+        FloatingWidget::AddTorrentConfirm => {
+            app.add_form.next();
+            return;
+        }
+        FloatingWidget::AddPreview => {
+            app.scroll_preview_down();
+            return;
+        }
+        FloatingWidget::VerifyResults => {
+            app.next_verify_file();
+            return;
+        }
+        FloatingWidget::CreateTorrent => {
+            if let Some(form) = app.create_form.as_mut() {
+                form.next();
+            }
+            return;
+        }
+        FloatingWidget::AddDuplicate
+        | FloatingWidget::CreateProgress
+        | FloatingWidget::VerifyProgress
+        | FloatingWidget::Help => return,
         FloatingWidget::ModifyColumns => {
             if let KeyModifiers::SHIFT = key.modifiers {
                 app.move_column_down();
@@ -88,23 +253,36 @@ fn handle_down(key: KeyEvent, app: &mut App<'static>) {
 
     match app.last_route_focused_widget() {
         Some(FocusableWidget::TorrentList) => app.next(),
+        Some(FocusableWidget::Sidebar) => app.next_category(),
         Some(FocusableWidget::Tabs) => {
-            if app.selected_tab != 1 {
-                return;
-            }
             let index = app.navigation_stack.len() - 1;
-            app.navigation_stack[index].focused_widget = FocusableWidget::FileList;
-            app.tree.next_file();
+            match app.selected_tab {
+                InfoTab::Files => {
+                    app.navigation_stack[index].focused_widget = FocusableWidget::FileList;
+                    app.tree.next_file();
+                }
+                InfoTab::Peers => {
+                    app.navigation_stack[index].focused_widget = FocusableWidget::PeerList;
+                    app.selected_peer = Some(0);
+                }
+                InfoTab::Trackers => {
+                    app.navigation_stack[index].focused_widget = FocusableWidget::TrackerList;
+                    app.selected_tracker = Some(0);
+                }
+                _ => (),
+            }
         }
         Some(FocusableWidget::FileList) => app.tree.next_file(),
+        Some(FocusableWidget::PeerList) => app.next_peer(),
+        Some(FocusableWidget::TrackerList) => app.next_tracker(),
         _ => (),
     }
 }
 
 fn handle_right(app: &mut App<'static>) {
     match app.floating_widget {
-        FloatingWidget::AddTorrent => {
-            app.floating_widget = FloatingWidget::AddTorrentConfirm;
+        FloatingWidget::AddTorrent | FloatingWidget::CreateSource => {
+            app.browser_enter();
             return;
         }
         FloatingWidget::ModifyColumns => {
@@ -115,7 +293,13 @@ fn handle_right(app: &mut App<'static>) {
             // app.sort_column = app.all_info_columns[app.selected_column.unwrap()].column;
             return;
         }
-        FloatingWidget::AddTorrentConfirm => return,
+        FloatingWidget::AddTorrentConfirm
+        | FloatingWidget::AddDuplicate
+        | FloatingWidget::AddPreview
+        | FloatingWidget::CreateTorrent
+        | FloatingWidget::CreateProgress
+        | FloatingWidget::VerifyProgress
+        | FloatingWidget::VerifyResults => return,
         _ => (),
     }
 
@@ -126,16 +310,46 @@ fn handle_right(app: &mut App<'static>) {
                 focused_widget: FocusableWidget::Tabs,
             });
         }
+        Some(FocusableWidget::Sidebar) => {
+            let index = app.navigation_stack.len() - 1;
+            app.navigation_stack[index].focused_widget = FocusableWidget::TorrentList;
+        }
         Some(FocusableWidget::Tabs) => app.next_tab(),
         Some(FocusableWidget::FileList) => app.tree.toggle_collapse(),
+        Some(FocusableWidget::PeerList) => app.next_peer_sort_column(),
         _ => (),
     }
 }
 
 fn handle_left(app: &mut App<'static>) {
     match app.floating_widget {
-        FloatingWidget::AddTorrentConfirm => {
-            app.floating_widget = FloatingWidget::AddTorrent;
+        FloatingWidget::AddPreview => {
+            app.toggle_preview();
+            return;
+        }
+        FloatingWidget::CreateTorrent => {
+            app.floating_widget = FloatingWidget::CreateSource;
+            return;
+        }
+        FloatingWidget::CreateProgress => return,
+        FloatingWidget::VerifyProgress | FloatingWidget::VerifyResults => {
+            app.floating_widget = FloatingWidget::AddTorrentConfirm;
+            return;
+        }
+        FloatingWidget::AddTorrent | FloatingWidget::CreateSource => {
+            app.browser.up();
+            return;
+        }
+        FloatingWidget::AddTorrentConfirm | FloatingWidget::AddDuplicate => {
+            match &app.add_source {
+                Some(AddSource::File(_)) => app.floating_widget = FloatingWidget::AddTorrent,
+                Some(source) => {
+                    app.input = source.filename();
+                    app.floating_widget = FloatingWidget::AddLink;
+                    app.input_mode = InputMode::Editing;
+                }
+                None => (),
+            }
             return;
         }
         FloatingWidget::ModifyColumns => {
@@ -147,8 +361,16 @@ fn handle_left(app: &mut App<'static>) {
         _ => (),
     }
 
-    if let Some(FocusableWidget::Tabs) = app.last_route_focused_widget() {
-        app.previous_tab();
+    match app.last_route_focused_widget() {
+        Some(FocusableWidget::Tabs) => app.previous_tab(),
+        Some(FocusableWidget::PeerList) => app.previous_peer_sort_column(),
+        Some(FocusableWidget::TorrentList)
+            if app.show_sidebar && matches!(app.floating_widget, FloatingWidget::None) =>
+        {
+            let index = app.navigation_stack.len() - 1;
+            app.navigation_stack[index].focused_widget = FocusableWidget::Sidebar;
+        }
+        _ => (),
     }
 }
 
@@ -166,36 +388,119 @@ async fn handle_pause(app: &mut App<'static>) {
         return;
     }
     if let Some(FocusableWidget::TorrentList) = app.last_route_focused_widget() {
-        app.toggle_torrent_pause().await;
+        if let Err(err) = app.toggle_torrent_pause().await {
+            log::error!("Could not pause torrent: {}", err);
+        }
     }
 }
 
 async fn handle_rename(app: &mut App<'static>) {
+    if !matches!(app.floating_widget, FloatingWidget::None)
+        || !app.connection_state.is_connected()
+        || app.visible_torrents.is_empty()
+    {
+        return;
+    }
+
+    match app.last_route_focused_widget() {
+        Some(FocusableWidget::TorrentList) => {
+            app.floating_widget = FloatingWidget::Input;
+            app.input_mode = InputMode::Editing;
+            app.input = app.get_selected_torrent_name();
+        }
+        Some(FocusableWidget::FileList) => {
+            let name = match app.tree.selected_node() {
+                Some(node) => node.name.to_owned(),
+                None => return,
+            };
+            app.floating_widget = FloatingWidget::Input;
+            app.input_mode = InputMode::Editing;
+            app.input = name;
+        }
+        Some(FocusableWidget::TrackerList) => {
+            let (id, announce) = match app.selected_tracker_stat() {
+                Some(stat) => (stat.id, stat.announce.to_owned()),
+                None => return,
+            };
+            app.floating_widget = FloatingWidget::TrackerInput;
+            app.input_mode = InputMode::Editing;
+            app.editing_tracker = Some(id);
+            app.input = announce;
+        }
+        _ => (),
+    }
+}
+
+fn handle_create(app: &mut App<'static>) {
     if !matches!(app.floating_widget, FloatingWidget::None) {
         return;
     }
 
     if let Some(FocusableWidget::TorrentList) = app.last_route_focused_widget() {
-        app.floating_widget = FloatingWidget::Input;
-        app.input_mode = InputMode::Editing;
-        app.input = app.get_selected_torrent_name();
+        app.open_create_torrent();
     }
 }
 
-async fn handle_add(app: &mut App<'static>) {
+fn handle_add_link(app: &mut App<'static>) {
+    if !matches!(app.floating_widget, FloatingWidget::None) || !app.connection_state.is_connected()
+    {
+        return;
+    }
+
     if let Some(FocusableWidget::TorrentList) = app.last_route_focused_widget() {
-        app.floating_widget = FloatingWidget::AddTorrent;
-        app.get_torrent_files();
+        app.open_add_link();
+    }
+}
+
+async fn handle_add(app: &mut App<'static>) {
+    if !app.connection_state.is_connected() {
+        return;
+    }
+
+    match app.last_route_focused_widget() {
+        Some(FocusableWidget::TorrentList) => {
+            app.floating_widget = FloatingWidget::AddTorrent;
+            app.browser.open(true);
+        }
+        Some(FocusableWidget::TrackerList) => {
+            if !matches!(app.floating_widget, FloatingWidget::None) {
+                return;
+            }
+            app.floating_widget = FloatingWidget::TrackerInput;
+            app.input_mode = InputMode::Editing;
+            app.editing_tracker = None;
+            app.input.clear();
+        }
+        _ => (),
+    }
+}
+
+fn handle_remove(app: &mut App<'static>) {
+    if !matches!(app.floating_widget, FloatingWidget::None)
+        || !app.connection_state.is_connected()
+        || app.visible_torrents.is_empty()
+    {
+        return;
+    }
+
+    match app.last_route_focused_widget() {
+        Some(FocusableWidget::TorrentList) => app.floating_widget = FloatingWidget::RemoveTorrent,
+        Some(FocusableWidget::TrackerList) if app.selected_tracker_stat().is_some() => {
+            app.floating_widget = FloatingWidget::RemoveTracker
+        }
+        _ => (),
     }
 }
 
-async fn handle_remove(app: &mut App<'static>) {
+async fn handle_reannounce(app: &mut App<'static>) {
     if !matches!(app.floating_widget, FloatingWidget::None) {
         return;
     }
 
-    if let Some(FocusableWidget::TorrentList) = app.last_route_focused_widget() {
-        app.floating_widget = FloatingWidget::RemoveTorrent;
+    if let Some(FocusableWidget::TrackerList) = app.last_route_focused_widget() {
+        if let Err(err) = app.reannounce_torrent().await {
+            log::error!("Could not reannounce torrent: {}", err);
+        }
     }
 }
 
@@ -205,6 +510,128 @@ fn handle_toggle(app: &mut App<'static>) {
     }
 }
 
+fn handle_profiles(app: &mut App<'static>) {
+    if !matches!(app.floating_widget, FloatingWidget::None) {
+        return;
+    }
+
+    app.selected_profile = Some(app.active_profile);
+    app.floating_widget = FloatingWidget::Profiles;
+}
+
+fn handle_filter(app: &mut App<'static>) {
+    if app.browsing() {
+        app.input_mode = InputMode::Editing;
+        app.input = app.browser.filter.to_owned();
+        return;
+    }
+
+    if !matches!(app.floating_widget, FloatingWidget::None) {
+        return;
+    }
+
+    if let Some(FocusableWidget::TorrentList) = app.last_route_focused_widget() {
+        app.floating_widget = FloatingWidget::Filter;
+        app.input_mode = InputMode::Editing;
+        app.input = app.filter_pattern.to_owned();
+    }
+}
+
+fn handle_saved_filters(app: &mut App<'static>) {
+    if !matches!(app.floating_widget, FloatingWidget::None) || app.config.filters.is_empty() {
+        return;
+    }
+
+    if let Some(FocusableWidget::TorrentList) = app.last_route_focused_widget() {
+        app.floating_widget = FloatingWidget::SavedFilters;
+    }
+}
+
+fn handle_sidebar(app: &mut App<'static>) {
+    if !matches!(app.floating_widget, FloatingWidget::None) {
+        return;
+    }
+
+    match app.last_route_focused_widget() {
+        Some(FocusableWidget::TorrentList) => app.show_sidebar = !app.show_sidebar,
+        Some(FocusableWidget::Sidebar) => {
+            app.show_sidebar = false;
+            let index = app.navigation_stack.len() - 1;
+            app.navigation_stack[index].focused_widget = FocusableWidget::TorrentList;
+        }
+        _ => (),
+    }
+}
+
+async fn handle_mark(app: &mut App<'static>) {
+    match app.floating_widget {
+        FloatingWidget::AddTorrentConfirm => {
+            app.select_add_row();
+            return;
+        }
+        FloatingWidget::CreateSource => {
+            app.pick_selected_source();
+            return;
+        }
+        FloatingWidget::CreateTorrent => {
+            app.select_create_row();
+            return;
+        }
+        _ => (),
+    }
+
+    if !matches!(app.floating_widget, FloatingWidget::None) {
+        return;
+    }
+
+    match app.last_route_focused_widget() {
+        Some(FocusableWidget::TorrentList) => app.toggle_mark(),
+        Some(FocusableWidget::FileList) => {
+            if let Err(err) = app.toggle_files_wanted().await {
+                log::error!("Could not change wanted files: {}", err);
+            }
+        }
+        _ => (),
+    }
+}
+
+async fn handle_priority(app: &mut App<'static>, priority: FilePriority) {
+    if let FloatingWidget::AddTorrentConfirm = app.floating_widget {
+        app.add_form.set_priority(priority);
+        return;
+    }
+
+    if !matches!(app.floating_widget, FloatingWidget::None) {
+        return;
+    }
+
+    if let Some(FocusableWidget::FileList) = app.last_route_focused_widget() {
+        if let Err(err) = app.set_files_priority(priority).await {
+            log::error!("Could not change file priority: {}", err);
+        }
+    }
+}
+
+fn handle_visual(app: &mut App<'static>) {
+    if !matches!(app.floating_widget, FloatingWidget::None) {
+        return;
+    }
+
+    if let Some(FocusableWidget::TorrentList) = app.last_route_focused_widget() {
+        app.toggle_visual();
+    }
+}
+
+fn handle_mark_all(app: &mut App<'static>) {
+    if !matches!(app.floating_widget, FloatingWidget::None) {
+        return;
+    }
+
+    if let Some(FocusableWidget::TorrentList) = app.last_route_focused_widget() {
+        app.toggle_mark_all();
+    }
+}
+
 fn handle_columns(app: &mut App<'static>) {
     if !matches!(app.floating_widget, FloatingWidget::None) {
         return;
@@ -214,42 +641,135 @@ fn handle_columns(app: &mut App<'static>) {
 }
 
 async fn handle_verify(app: &mut App<'static>) {
+    if let FloatingWidget::AddTorrentConfirm = app.floating_widget {
+        app.start_local_verify();
+        return;
+    }
+
     if !matches!(app.floating_widget, FloatingWidget::None) {
         return;
     }
 
     if let FocusableWidget::TorrentList = app.last_route_focused_widget().unwrap() {
-        app.verify_torrent().await;
+        if let Err(err) = app.verify_torrent().await {
+            log::error!("Could not verify torrent: {}", err);
+        }
     }
 }
 
 async fn handle_enter(app: &mut App<'static>) {
     match app.floating_widget {
+        FloatingWidget::AddTorrent | FloatingWidget::CreateSource => app.browser_enter(),
+        FloatingWidget::CreateTorrent => app.start_create_torrent(),
         FloatingWidget::AddTorrentConfirm => {
-            app.add_torrent().await;
+            if let Err(err) = app.add_torrent().await {
+                log::error!("Could not add torrent: {}", err);
+            }
+            app.floating_widget = FloatingWidget::None;
+        }
+        FloatingWidget::AddDuplicate => {
+            if let Err(err) = app.merge_trackers().await {
+                log::error!("Could not merge trackers: {}", err);
+            }
             app.floating_widget = FloatingWidget::None;
         }
         FloatingWidget::RemoveTorrent => {
-            app.remove_torrent().await;
+            if let Err(err) = app.remove_torrent().await {
+                log::error!("Could not remove torrent: {}", err);
+            }
+            app.floating_widget = FloatingWidget::None;
+        }
+        FloatingWidget::RemoveTracker => {
+            if let Err(err) = app.remove_tracker().await {
+                log::error!("Could not remove tracker: {}", err);
+            }
             app.floating_widget = FloatingWidget::None;
         }
         FloatingWidget::ModifyColumns => {
             app.toggle_show_column();
         }
+        FloatingWidget::Profiles => {
+            app.switch_profile();
+            app.floating_widget = FloatingWidget::None;
+        }
+        FloatingWidget::SavedFilters => {
+            app.apply_saved_filter();
+            app.floating_widget = FloatingWidget::None;
+        }
+        FloatingWidget::None => {
+            if let Some(FocusableWidget::PeerList) = app.last_route_focused_widget() {
+                app.toggle_peer_sort_order();
+            }
+        }
         _ => (),
     }
 }
 
 fn handle_esc(app: &mut App<'static>) {
+    // Leaving the prompts of the add dialogs keeps the dialog open
+    match (&app.floating_widget, &app.input_mode) {
+        (FloatingWidget::AddTorrent | FloatingWidget::CreateSource, InputMode::Editing) => {
+            app.browser.set_filter("");
+            app.input_mode = InputMode::Normal;
+            return;
+        }
+        (FloatingWidget::AddTorrentConfirm, InputMode::Editing) => {
+            app.add_error = None;
+            app.input_mode = InputMode::Normal;
+            return;
+        }
+        (FloatingWidget::CreateTorrent, InputMode::Editing) => {
+            if let Some(form) = app.create_form.as_mut() {
+                form.error = None;
+            }
+            app.input_mode = InputMode::Normal;
+            return;
+        }
+        (FloatingWidget::AddPreview, _) => {
+            app.toggle_preview();
+            return;
+        }
+        _ => (),
+    }
+
     match app.last_route_focused_widget() {
         Some(FocusableWidget::Tabs) => {
             app.stack_pop();
             return;
         }
         Some(FocusableWidget::FileList) => {
+            if matches!(app.floating_widget, FloatingWidget::None) {
+                let index = app.navigation_stack.len() - 1;
+                app.navigation_stack[index].focused_widget = FocusableWidget::Tabs;
+                app.tree.state = TreeState::default();
+            }
+        }
+        Some(FocusableWidget::PeerList) => {
             let index = app.navigation_stack.len() - 1;
             app.navigation_stack[index].focused_widget = FocusableWidget::Tabs;
-            app.tree.state = TreeState::default();
+        }
+        Some(FocusableWidget::TrackerList) => {
+            if matches!(app.floating_widget, FloatingWidget::None) {
+                let index = app.navigation_stack.len() - 1;
+                app.navigation_stack[index].focused_widget = FocusableWidget::Tabs;
+            }
+        }
+        Some(FocusableWidget::Sidebar) => {
+            let index = app.navigation_stack.len() - 1;
+            app.navigation_stack[index].focused_widget = FocusableWidget::TorrentList;
+        }
+        Some(FocusableWidget::TorrentList) => {
+            // Esc drops the marks first and the filter on the next press
+            if matches!(app.floating_widget, FloatingWidget::None) && app.clear_marks() {
+                return;
+            }
+            if matches!(
+                app.floating_widget,
+                FloatingWidget::None | FloatingWidget::Filter
+            ) {
+                app.input.clear();
+                app.set_filter("");
+            }
         }
         _ => (),
     }
@@ -0,0 +1,184 @@
+use std::{process::Stdio, time::Duration};
+use tokio::{process::Command, time::timeout};
+
+pub struct Magnet {
+    pub uri: String,
+    pub hash: String,
+    pub name: Option<String>,
+    pub trackers: Vec<String>,
+}
+
+impl Magnet {
+    // Only the info hash is required, everything else is informational
+    pub fn parse(uri: &str) -> Result<Magnet, String> {
+        let query = match uri.strip_prefix("magnet:?") {
+            Some(query) => query,
+            None => return Err("not a magnet link".to_string()),
+        };
+
+        let mut hash = None;
+        let mut name = None;
+        let mut trackers = Vec::new();
+
+        for pair in query.split('&') {
+            let (key, value) = match pair.split_once('=') {
+                Some(pair) => pair,
+                None => continue,
+            };
+
+            match key {
+                "xt" if hash.is_none() => hash = parse_hash(value)?,
+                "dn" => name = Some(percent_decode(value)),
+                "tr" => trackers.push(percent_decode(value)),
+                _ => (),
+            }
+        }
+
+        match hash {
+            Some(hash) => Ok(Magnet {
+                uri: uri.to_string(),
+                hash,
+                name,
+                trackers,
+            }),
+            None => Err("magnet link has no btih or btmh hash".to_string()),
+        }
+    }
+
+    // The v1 info hash as hex, which is what the daemon reports as hashString
+    pub fn info_hash(&self) -> Option<String> {
+        match self.hash.len() {
+            40 => Some(self.hash.to_owned()),
+            32 => Some(base32_to_hex(&self.hash)),
+            _ => None,
+        }
+    }
+}
+
+fn base32_to_hex(hash: &str) -> String {
+    let mut hex = String::new();
+    let mut bits: u64 = 0;
+    let mut count = 0;
+
+    for c in hash.chars() {
+        let value = match c.to_ascii_uppercase() {
+            c @ 'A'..='Z' => c as u64 - 'A' as u64,
+            c => c as u64 - '2' as u64 + 26,
+        };
+        bits = (bits << 5) | value;
+        count += 5;
+        if count >= 8 {
+            count -= 8;
+            hex.push_str(&format!("{:02x}", (bits >> count) & 0xff));
+            bits &= (1 << count) - 1;
+        }
+    }
+
+    hex
+}
+
+fn parse_hash(xt: &str) -> Result<Option<String>, String> {
+    if let Some(hash) = xt.strip_prefix("urn:btih:") {
+        let valid = (hash.len() == 40 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+            || (hash.len() == 32
+                && hash
+                    .chars()
+                    .all(|c| matches!(c.to_ascii_uppercase(), 'A'..='Z' | '2'..='7')));
+        if !valid {
+            return Err(format!("invalid btih hash '{}'", hash));
+        }
+        return Ok(Some(hash.to_lowercase()));
+    }
+
+    // v2 hashes are sha256 multihashes, 0x12 0x20 followed by the digest
+    if let Some(hash) = xt.strip_prefix("urn:btmh:") {
+        let valid = hash.len() == 68
+            && hash.starts_with("1220")
+            && hash.chars().all(|c| c.is_ascii_hexdigit());
+        if !valid {
+            return Err(format!("invalid btmh hash '{}'", hash));
+        }
+        return Ok(Some(hash.to_lowercase()));
+    }
+
+    Ok(None)
+}
+
+fn percent_decode(value: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut rest = value.bytes();
+
+    while let Some(byte) = rest.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex: Vec<u8> = rest.by_ref().take(2).collect();
+                match std::str::from_utf8(&hex)
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(decoded) => bytes.push(decoded),
+                    None => {
+                        bytes.push(b'%');
+                        bytes.extend(hex);
+                    }
+                }
+            }
+            byte => bytes.push(byte),
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+pub fn is_url(link: &str) -> bool {
+    link.starts_with("http://") || link.starts_with("https://")
+}
+
+const CLIPBOARD_COMMANDS: [&str; 4] = [
+    "wl-paste --no-newline",
+    "xclip -out -selection clipboard",
+    "xsel --output --clipboard",
+    "pbpaste",
+];
+
+const CLIPBOARD_TIMEOUT: Duration = Duration::from_millis(500);
+
+// Uses `command` if set, otherwise the first clipboard tool that works
+pub async fn read_clipboard(command: Option<String>) -> Option<String> {
+    if let Some(command) = command {
+        return run_clipboard_command(&command).await;
+    }
+
+    for command in CLIPBOARD_COMMANDS {
+        if let Some(text) = run_clipboard_command(command).await {
+            return Some(text);
+        }
+    }
+    None
+}
+
+// The tool is started without a shell so that a hanging one, like xclip when
+// nothing owns the selection, is itself killed after CLIPBOARD_TIMEOUT
+async fn run_clipboard_command(command: &str) -> Option<String> {
+    let mut words = command.split_whitespace();
+    let child = Command::new(words.next()?)
+        .args(words)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .ok()?;
+
+    match timeout(CLIPBOARD_TIMEOUT, child.wait_with_output()).await {
+        Ok(Ok(output)) if output.status.success() => {
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        }
+        Ok(_) => None,
+        Err(_) => {
+            log::warn!("Clipboard command '{}' did not finish in time", command);
+            None
+        }
+    }
+}
@@ -1,14 +1,29 @@
+mod add_form;
 mod app;
+mod browser;
+mod cli;
 mod config;
 mod conversion;
+mod create;
+mod filter;
 mod io_handler;
 mod key_handlers;
+mod magnet;
+mod netrc;
+mod preview;
 mod tree;
 mod ui;
+mod verify;
+mod watch;
 
 use crate::ui::draw;
-use app::{get_all_torrents, App};
+use app::{finish_create_torrent, get_all_torrents, App};
+use clap::Parser;
+use cli::Args;
+use config::Config;
 use crossterm::{
+    cursor::Show,
+    event::KeyEvent,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
@@ -17,6 +32,7 @@ use key_handlers::handler;
 use log::LevelFilter;
 use std::{
     io::{self, Write},
+    panic,
     sync::{Arc, Mutex},
     time::Duration,
 };
@@ -24,23 +40,50 @@ use tui::{backend::CrosstermBackend, Terminal};
 
 #[tokio::main]
 async fn main() -> io::Result<()> {
-    tui_logger::init_logger(LevelFilter::Error).unwrap();
-    tui_logger::set_default_level(log::LevelFilter::Trace);
+    let args = Args::parse();
+
+    // Before the config is read, so its warnings end up in the log
+    if args.command.is_some() {
+        cli::init_logger();
+    } else {
+        tui_logger::init_logger(LevelFilter::Info).unwrap();
+        tui_logger::set_default_level(log::LevelFilter::Trace);
+    }
+
+    let mut config = Config::new();
+    args.apply(&mut config);
 
-    let app = Arc::new(Mutex::new(App::new().await));
+    if let Some(command) = args.command {
+        let code = cli::run(command, App::new(config)).await;
+        std::process::exit(code);
+    }
+
+    let watch_dirs = std::mem::take(&mut config.watch);
+    let app = Arc::new(Mutex::new(App::new(config)));
     let app_ui = Arc::clone(&app);
 
+    if !watch_dirs.is_empty() {
+        tokio::spawn(watch::watch(Arc::clone(&app), watch_dirs));
+    }
+
     {
         get_all_torrents(&app).await;
         let mut app = app.lock().unwrap();
-        app.tree_with_path();
+        app.build_file_tree();
     }
 
     tokio::spawn(async move {
         loop {
             let app = app.clone();
             get_all_torrents(&app).await;
-            tokio::time::sleep(Duration::from_secs(1)).await;
+            let (delay, notify) = {
+                let app = app.lock().unwrap();
+                (app.connection_state.retry_delay(), app.poll_notify.clone())
+            };
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => (),
+                _ = notify.notified() => (),
+            }
         }
     });
 
@@ -65,18 +108,34 @@ async fn start_ui(app: &Arc<Mutex<App<'static>>>) -> io::Result<()> {
         }
 
         match events.next().await {
-            InputEvent::Input(key) => {
-                let mut app = app_mutex.lock().unwrap();
-                handler(key, &mut app).await;
+            InputEvent::Input(key) => handle_key(&app_mutex, key).await,
+            InputEvent::Tick => {
+                let created = {
+                    let mut app = app_mutex.lock().unwrap();
+                    if let Some(form) = app.create_form.as_mut() {
+                        form.update_size();
+                    }
+                    app.finish_local_verify();
+                    app.finish_clipboard_read();
+                    app.take_finished_create()
+                };
+                if let Some((job, client)) = created {
+                    tokio::spawn(finish_create_torrent(job, client));
+                }
             }
-            InputEvent::Tick => {}
         }
 
         {
             let mut app = app_mutex.lock().unwrap();
-            let download = app.session_stats.as_ref().unwrap().download_speed;
-            let upload = app.session_stats.as_ref().unwrap().upload_speed;
-            app.data.on_tick(download, upload);
+            let speeds = app
+                .session_stats
+                .as_ref()
+                .map(|stats| (stats.download_speed, stats.upload_speed));
+            if app.connection_state.is_connected() {
+                if let Some((download, upload)) = speeds {
+                    app.data.on_tick(download, upload);
+                }
+            }
 
             if app.should_quit {
                 events.close();
@@ -87,14 +146,34 @@ async fn start_ui(app: &Arc<Mutex<App<'static>>>) -> io::Result<()> {
 
     terminal.clear()?;
     terminal.show_cursor()?;
-    disable_raw_mode()?;
-    io::stdout().execute(LeaveAlternateScreen)?;
-    Ok(())
+    restore_terminal()
+}
+
+// The handlers need the whole App across their RPC calls, so the poller
+// waits until the key press has been handled
+#[allow(clippy::await_holding_lock)]
+async fn handle_key(app: &Mutex<App<'static>>, key: KeyEvent) {
+    let mut app = app.lock().unwrap();
+    handler(key, &mut app).await;
 }
 
 fn setup_terminal() -> io::Result<()> {
     enable_raw_mode()?;
     io::stdout().execute(EnterAlternateScreen)?;
+
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal();
+        default_hook(info);
+    }));
+
+    Ok(())
+}
+
+fn restore_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    io::stdout().execute(LeaveAlternateScreen)?;
+    io::stdout().execute(Show)?;
     Ok(())
 }
 
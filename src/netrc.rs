@@ -0,0 +1,189 @@
+use std::{fs, path::Path};
+
+pub struct NetrcEntry {
+    pub machine: Option<String>,
+    pub login: Option<String>,
+    pub password: Option<String>,
+}
+
+pub fn lookup(path: &Path, host: &str) -> Option<NetrcEntry> {
+    let contents = fs::read_to_string(path).ok()?;
+    find(parse(&contents), host)
+}
+
+// The entry for `host`, or the `default` entry wherever it is in the file
+fn find(mut entries: Vec<NetrcEntry>, host: &str) -> Option<NetrcEntry> {
+    if let Some(index) = entries
+        .iter()
+        .position(|entry| entry.machine.as_deref() == Some(host))
+    {
+        return Some(entries.swap_remove(index));
+    }
+
+    entries.into_iter().find(|entry| entry.machine.is_none())
+}
+
+fn parse(contents: &str) -> Vec<NetrcEntry> {
+    let mut entries: Vec<NetrcEntry> = Vec::new();
+    let mut tokens = Tokens { rest: contents };
+
+    while let Some(token) = tokens.next() {
+        match token.as_str() {
+            "machine" | "default" => {
+                let machine = if token == "machine" {
+                    tokens.next()
+                } else {
+                    None
+                };
+                entries.push(NetrcEntry {
+                    machine,
+                    login: None,
+                    password: None,
+                });
+            }
+            "login" => {
+                let login = tokens.next();
+                if let Some(entry) = entries.last_mut() {
+                    entry.login = login;
+                }
+            }
+            "password" => {
+                let password = tokens.next();
+                if let Some(entry) = entries.last_mut() {
+                    entry.password = password;
+                }
+            }
+            "account" => {
+                tokens.next();
+            }
+            "macdef" => tokens.skip_macro(),
+            _ => (),
+        }
+    }
+
+    entries
+}
+
+// Tokens are separated by whitespace, double quotes allow whitespace in them
+// and a backslash escapes the next character inside quotes
+struct Tokens<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Tokens<'a> {
+    fn next(&mut self) -> Option<String> {
+        self.rest = self.rest.trim_start();
+        let mut chars = self.rest.char_indices();
+        let (_, first) = chars.next()?;
+
+        if first != '"' {
+            let end = self
+                .rest
+                .find(char::is_whitespace)
+                .unwrap_or(self.rest.len());
+            let token = self.rest[..end].to_string();
+            self.rest = &self.rest[end..];
+            return Some(token);
+        }
+
+        let mut token = String::new();
+        let mut end = self.rest.len();
+        let mut escaped = false;
+        for (i, c) in chars {
+            match c {
+                _ if escaped => {
+                    token.push(c);
+                    escaped = false;
+                }
+                '\\' => escaped = true,
+                '"' => {
+                    end = i + 1;
+                    break;
+                }
+                _ => token.push(c),
+            }
+        }
+        self.rest = &self.rest[end..];
+        Some(token)
+    }
+
+    // Macro definitions continue until the next empty line
+    fn skip_macro(&mut self) {
+        let mut offset = match self.rest.find('\n') {
+            Some(index) => index + 1,
+            None => self.rest.len(),
+        };
+        for line in self.rest[offset..].split_inclusive('\n') {
+            offset += line.len();
+            if line.trim().is_empty() {
+                break;
+            }
+        }
+        self.rest = &self.rest[offset..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credentials(contents: &str, host: &str) -> Option<(String, String)> {
+        let entry = find(parse(contents), host)?;
+        Some((entry.login?, entry.password?))
+    }
+
+    fn pair(login: &str, password: &str) -> Option<(String, String)> {
+        Some((login.to_string(), password.to_string()))
+    }
+
+    #[test]
+    fn machine_before_default() {
+        let contents = "default login anon password guest\n\
+                        machine nas login admin password secret\n";
+        assert_eq!(credentials(contents, "nas"), pair("admin", "secret"));
+        assert_eq!(credentials(contents, "seedbox"), pair("anon", "guest"));
+    }
+
+    #[test]
+    fn no_default() {
+        let contents = "machine nas login admin password secret";
+        assert_eq!(credentials(contents, "seedbox"), None);
+    }
+
+    #[test]
+    fn entries_on_several_lines() {
+        let contents = "machine nas\n  login admin\n  account home\n  password secret\n";
+        assert_eq!(credentials(contents, "nas"), pair("admin", "secret"));
+    }
+
+    #[test]
+    fn macdef_is_skipped() {
+        let contents = "machine ftp login bob password one\n\
+                        macdef init\n\
+                        machine nas login mallory password evil\n\
+                        \n\
+                        machine nas login admin password secret\n";
+        assert_eq!(credentials(contents, "nas"), pair("admin", "secret"));
+        assert_eq!(credentials(contents, "ftp"), pair("bob", "one"));
+    }
+
+    #[test]
+    fn macdef_until_end_of_file() {
+        let contents = "machine nas login admin password secret\n\
+                        macdef init\n\
+                        default login anon password guest";
+        assert_eq!(credentials(contents, "seedbox"), None);
+    }
+
+    #[test]
+    fn quoted_tokens() {
+        let contents = r#"machine nas login "the admin" password "p a\"s\\s""#;
+        assert_eq!(credentials(contents, "nas"), pair("the admin", r#"p a"s\s"#));
+    }
+
+    #[test]
+    fn unterminated_quote() {
+        let contents = r#"machine nas login admin password "secret"#;
+        assert_eq!(credentials(contents, "nas"), pair("admin", "secret"));
+    }
+}
@@ -0,0 +1,205 @@
+use std::{collections::BTreeMap, path::Path};
+
+use lava_torrent::{bencode::BencodeElem, torrent::v1::Torrent};
+
+use crate::{
+    add_form::announce_urls,
+    conversion::{convert_bytes, date},
+};
+
+pub enum PreviewLine {
+    Section(String),
+    Field(&'static str, String),
+    Item(String),
+    File {
+        depth: usize,
+        name: String,
+        size: i64,
+    },
+}
+
+// What the add dialog shows of a .torrent file before it's added
+pub struct Preview {
+    pub name: String,
+    pub info_hash: String,
+    pub size: i64,
+    pub lines: Vec<PreviewLine>,
+}
+
+impl Preview {
+    pub fn new(torrent: &Torrent) -> Preview {
+        let mut lines = vec![
+            PreviewLine::Section("General".to_string()),
+            PreviewLine::Field("Name", torrent.name.to_owned()),
+            PreviewLine::Field("Info hash", torrent.info_hash()),
+            PreviewLine::Field("Version", version(torrent).to_string()),
+            PreviewLine::Field("Size", convert_bytes(torrent.length)),
+            PreviewLine::Field(
+                "Pieces",
+                format!(
+                    "{} x {}",
+                    torrent.pieces.len(),
+                    convert_bytes(torrent.piece_length)
+                ),
+            ),
+            PreviewLine::Field("Private", torrent.is_private().to_string()),
+        ];
+        if let Some(BencodeElem::Integer(created)) = extra_field(torrent, "creation date") {
+            lines.push(PreviewLine::Field("Created", date(*created)));
+        }
+        if let Some(created_by) = extra_string(torrent, "created by") {
+            lines.push(PreviewLine::Field("Created by", created_by));
+        }
+        if let Some(comment) = extra_string(torrent, "comment") {
+            lines.push(PreviewLine::Field("Comment", comment));
+        }
+
+        let tiers = match &torrent.announce_list {
+            Some(tiers) => tiers.to_owned(),
+            None => vec![announce_urls(torrent)],
+        };
+        lines.push(PreviewLine::Section("Trackers".to_string()));
+        for (i, tier) in tiers.iter().filter(|tier| !tier.is_empty()).enumerate() {
+            lines.push(PreviewLine::Item(format!("Tier {}", i + 1)));
+            for tracker in tier {
+                lines.push(PreviewLine::Item(format!("  {}", tracker)));
+            }
+        }
+
+        let web_seeds = web_seeds(torrent);
+        if !web_seeds.is_empty() {
+            lines.push(PreviewLine::Section("Web seeds".to_string()));
+            lines.extend(web_seeds.into_iter().map(PreviewLine::Item));
+        }
+
+        let files = torrent.files.as_ref().map_or(1, |files| files.len());
+        lines.push(PreviewLine::Section(format!("Files ({})", files)));
+        let mut root = Dir::default();
+        match &torrent.files {
+            Some(files) => {
+                for file in files {
+                    let mut parts = vec![torrent.name.to_owned()];
+                    parts.extend(
+                        file.path
+                            .iter()
+                            .map(|part| part.to_string_lossy().to_string()),
+                    );
+                    root.insert(&parts, file.length);
+                }
+            }
+            None => root.insert(&[torrent.name.to_owned()], torrent.length),
+        }
+        root.push_lines(0, &mut lines);
+
+        Preview {
+            name: torrent.name.to_owned(),
+            info_hash: torrent.info_hash(),
+            size: torrent.length,
+            lines,
+        }
+    }
+}
+
+// lava_torrent only reads v1 metadata, a hybrid torrent additionally has
+// `meta version` 2 in its info dictionary
+fn version(torrent: &Torrent) -> &'static str {
+    let meta_version = torrent
+        .extra_info_fields
+        .as_ref()
+        .and_then(|fields| fields.get("meta version"));
+    match meta_version {
+        Some(BencodeElem::Integer(2)) => "hybrid (v1 + v2)",
+        _ => "v1",
+    }
+}
+
+// Torrents with only v2 metadata can't be read at all, they're recognised from
+// the raw bencode so the add dialog can say why
+pub fn read_error(path: &Path) -> String {
+    let v2_only = match BencodeElem::from_file(path).as_deref() {
+        Ok([BencodeElem::Dictionary(torrent)]) => match torrent.get("info") {
+            Some(BencodeElem::Dictionary(info)) => {
+                matches!(info.get("meta version"), Some(BencodeElem::Integer(2)))
+                    && !info.contains_key("pieces")
+            }
+            _ => false,
+        },
+        _ => false,
+    };
+
+    if v2_only {
+        "v2 only torrent, only v1 and hybrid torrents are supported".to_string()
+    } else {
+        "Could not read the torrent file".to_string()
+    }
+}
+
+fn extra_field<'a>(torrent: &'a Torrent, key: &str) -> Option<&'a BencodeElem> {
+    torrent.extra_fields.as_ref()?.get(key)
+}
+
+fn extra_string(torrent: &Torrent, key: &str) -> Option<String> {
+    match extra_field(torrent, key)? {
+        BencodeElem::String(value) => Some(value.to_owned()),
+        BencodeElem::Bytes(value) => Some(String::from_utf8_lossy(value).to_string()),
+        _ => None,
+    }
+}
+
+// `url-list` is either a single URL or a list of them
+fn web_seeds(torrent: &Torrent) -> Vec<String> {
+    match extra_field(torrent, "url-list") {
+        Some(BencodeElem::String(url)) => vec![url.to_owned()],
+        Some(BencodeElem::List(urls)) => urls
+            .iter()
+            .filter_map(|url| match url {
+                BencodeElem::String(url) => Some(url.to_owned()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[derive(Default)]
+struct Dir {
+    size: i64,
+    dirs: BTreeMap<String, Dir>,
+    files: BTreeMap<String, i64>,
+}
+
+impl Dir {
+    fn insert(&mut self, parts: &[String], length: i64) {
+        self.size += length;
+        match parts {
+            [name] => {
+                self.files.insert(name.to_owned(), length);
+            }
+            [dir, rest @ ..] => self
+                .dirs
+                .entry(dir.to_owned())
+                .or_default()
+                .insert(rest, length),
+            [] => (),
+        }
+    }
+
+    // Directories first, like the Files tab
+    fn push_lines(&self, depth: usize, lines: &mut Vec<PreviewLine>) {
+        for (name, dir) in &self.dirs {
+            lines.push(PreviewLine::File {
+                depth,
+                name: format!("{}/", name),
+                size: dir.size,
+            });
+            dir.push_lines(depth + 1, lines);
+        }
+        for (name, size) in &self.files {
+            lines.push(PreviewLine::File {
+                depth,
+                name: name.to_owned(),
+                size: *size,
+            });
+        }
+    }
+}
@@ -1,12 +1,29 @@
-use std::{fs, path::PathBuf, str::FromStr};
+use std::path::{Path, PathBuf};
 
+use transmission_rpc::types::Torrent;
 use tui_tree_widget::{flatten, TreeItem, TreeState};
+use unicode_width::UnicodeWidthChar;
 
-use crate::app::App;
+use crate::conversion::convert_bytes;
+
+// Width of the size, done and progress columns at the end of each item
+pub const STATS_WIDTH: usize = 28;
 
 pub struct StatefulTree<'a> {
     pub state: TreeState,
     pub items: Vec<TreeItem<'a>>,
+    pub nodes: Vec<FileNode>,
+    pub width: usize,
+}
+
+// Mirrors `items`, with each node's path relative to the download directory
+// and the indices of the torrent files inside it
+pub struct FileNode {
+    pub name: String,
+    pub path: PathBuf,
+    pub files: Vec<usize>,
+    pub missing: bool,
+    pub children: Vec<FileNode>,
 }
 
 impl<'a> StatefulTree<'a> {
@@ -14,11 +31,45 @@ impl<'a> StatefulTree<'a> {
         Self {
             state: TreeState::default(),
             items: Vec::new(),
+            nodes: Vec::new(),
+            width: 0,
+        }
+    }
+
+    pub fn selected_node(&self) -> Option<&FileNode> {
+        let mut nodes = &self.nodes;
+        let mut node = None;
+        for index in self.state.selected() {
+            let child = nodes.get(index)?;
+            nodes = &child.children;
+            node = Some(child);
         }
+        node
+    }
+
+    // Renames the selected node in place, keeping the selection
+    pub fn rename_selected(&mut self, name: &str) {
+        let node = match node_mut(&mut self.nodes, &self.state.selected()) {
+            Some(node) => node,
+            None => return,
+        };
+
+        node.name = name.to_string();
+        node.path = node.path.with_file_name(name);
+        reparent(&mut node.children, &node.path);
+    }
+
+    // Rebuilds the items so the markers and columns follow the torrent's
+    // current file state
+    pub fn refresh(&mut self, torrent: &Torrent) {
+        self.items = tree_items(&self.nodes, torrent, self.width, 0);
     }
 
     pub fn next_file(&mut self) {
         let visible = flatten(&self.state.get_all_opened(), &self.items);
+        if visible.is_empty() {
+            return;
+        }
         let current_identifier = self.state.selected();
         let current_index = visible
             .iter()
@@ -32,6 +83,9 @@ impl<'a> StatefulTree<'a> {
 
     pub fn previous_file(&mut self) {
         let visible = flatten(&self.state.get_all_opened(), &self.items);
+        if visible.is_empty() {
+            return;
+        }
         let current_identifier = self.state.selected();
         let current_index = visible
             .iter()
@@ -48,71 +102,203 @@ impl<'a> StatefulTree<'a> {
     }
 }
 
-pub fn make_tree(
-    path: PathBuf,
-    app: &App,
-    files_done: &mut Vec<PathBuf>,
+fn node_mut<'n>(nodes: &'n mut [FileNode], identifier: &[usize]) -> Option<&'n mut FileNode> {
+    let (first, rest) = identifier.split_first()?;
+    let node = nodes.get_mut(*first)?;
+    if rest.is_empty() {
+        Some(node)
+    } else {
+        node_mut(&mut node.children, rest)
+    }
+}
+
+fn reparent(nodes: &mut [FileNode], parent: &Path) {
+    for node in nodes {
+        node.path = parent.join(&node.name);
+        reparent(&mut node.children, &node.path);
+    }
+}
+
+// Builds the tree from the file list reported by the daemon. When
+// `download_dir` is given, files that can't be found under it are marked as
+// missing
+pub fn make_tree(torrent: &Torrent, download_dir: Option<&Path>) -> Vec<FileNode> {
+    let mut nodes = Vec::new();
+    for (index, file) in torrent.files.as_ref().unwrap().iter().enumerate() {
+        let missing = matches!(download_dir, Some(dir) if !dir.join(&file.name).exists());
+        insert_file(
+            &mut nodes,
+            Path::new(&file.name),
+            Path::new(""),
+            index,
+            missing,
+        );
+    }
+    sort_nodes(&mut nodes);
+    nodes
+}
+
+fn insert_file(nodes: &mut Vec<FileNode>, path: &Path, parent: &Path, index: usize, missing: bool) {
+    let mut components = path.components();
+    let name = match components.next() {
+        Some(component) => component.as_os_str().to_string_lossy().to_string(),
+        None => return,
+    };
+    let rest = components.as_path();
+
+    let position = match nodes.iter().position(|node| node.name == name) {
+        Some(position) => position,
+        None => {
+            nodes.push(FileNode {
+                path: parent.join(&name),
+                name,
+                files: Vec::new(),
+                missing: false,
+                children: Vec::new(),
+            });
+            nodes.len() - 1
+        }
+    };
+
+    let node = &mut nodes[position];
+    node.files.push(index);
+    if rest.as_os_str().is_empty() {
+        node.missing = missing;
+    } else {
+        insert_file(&mut node.children, rest, &node.path, index, missing);
+    }
+}
+
+// Directories first, then by name
+fn sort_nodes(nodes: &mut [FileNode]) {
+    nodes.sort_by(|a, b| {
+        a.children
+            .is_empty()
+            .cmp(&b.children.is_empty())
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    for node in nodes {
+        sort_nodes(&mut node.children);
+    }
+}
+
+fn tree_items(
+    nodes: &[FileNode],
+    torrent: &Torrent,
+    width: usize,
+    depth: usize,
 ) -> Vec<TreeItem<'static>> {
-    let mut files_in_dir = Vec::new();
-    let readdir = fs::read_dir(&path);
-    if readdir.is_err() {
-        return vec![TreeItem::new_leaf(
-            path.file_name().unwrap().to_string_lossy().to_string(),
-        )];
-    }
-
-    let mut paths: Vec<_> = readdir.unwrap().map(|r| r.unwrap()).collect();
-    paths.sort_by_key(|f| f.path());
-    paths.sort_by_key(|f| !f.path().is_dir());
-
-    'outer: for file in paths {
-        for done in &*files_done {
-            if file.path().starts_with(done) {
-                continue 'outer;
+    nodes
+        .iter()
+        .map(|node| {
+            let mut text = format!(
+                "{} {}{}",
+                wanted_marker(node, torrent),
+                node.name,
+                priority_marker(node, torrent)
+            );
+            if node.missing {
+                text.push_str(" (missing)");
             }
-        }
 
-        let mut is_torrent_file = false;
-        for torrent_file in app.get_selected_torrent().files.as_ref().unwrap() {
-            let download_dir = app.get_selected_torrent().download_dir.as_deref().unwrap();
-            let mut torrent_file_path = PathBuf::from_str(download_dir).unwrap();
-            torrent_file_path = torrent_file_path.join(&torrent_file.name);
-
-            if file.path().starts_with(&torrent_file_path)
-                || torrent_file_path.starts_with(file.path())
-            {
-                is_torrent_file = true;
-                break;
+            // The tree indents each level by two columns and puts a two column
+            // symbol in front of every item
+            let text = with_stats(
+                text,
+                stats(node, torrent),
+                width.saturating_sub(depth * 2 + 2),
+            );
+            if node.children.is_empty() {
+                TreeItem::new_leaf(text)
+            } else {
+                TreeItem::new(text, tree_items(&node.children, torrent, width, depth + 1))
             }
-        }
+        })
+        .collect()
+}
+
+fn stats(node: &FileNode, torrent: &Torrent) -> String {
+    let files = torrent.files.as_ref().unwrap();
+    let size: i64 = node.files.iter().map(|index| files[*index].length).sum();
+    let done: i64 = node
+        .files
+        .iter()
+        .map(|index| files[*index].bytes_completed)
+        .sum();
+    let percent = if size == 0 {
+        100.0
+    } else {
+        done as f64 / size as f64 * 100.0
+    };
 
-        if !is_torrent_file {
-            files_done.push(file.path());
-            continue;
+    format!(
+        "{:>10} {:>10} {:>5.1}%",
+        convert_bytes(size),
+        convert_bytes(done),
+        percent
+    )
+}
+
+// Pads or cuts the text so the stats line up at the right edge
+fn with_stats(text: String, stats: String, width: usize) -> String {
+    if width <= STATS_WIDTH {
+        return text;
+    }
+
+    let name_width = width - STATS_WIDTH;
+    let mut line = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width > name_width {
+            break;
         }
+        line.push(c);
+        used += char_width;
+    }
+    line.push_str(&" ".repeat(name_width - used));
+    line.push_str(&stats);
+    line
+}
 
-        let item = if file.path().is_dir() {
-            let children = make_tree(file.path(), app, files_done);
-            TreeItem::new(
-                file.path()
-                    .file_name()
-                    .unwrap()
-                    .to_string_lossy()
-                    .to_string(),
-                children,
-            )
-        } else {
-            TreeItem::new_leaf(
-                file.path()
-                    .file_name()
-                    .unwrap()
-                    .to_string_lossy()
-                    .to_string(),
-            )
-        };
+fn wanted_marker(node: &FileNode, torrent: &Torrent) -> &'static str {
+    let stats = match torrent.file_stats.as_ref() {
+        Some(stats) => stats,
+        None => return "",
+    };
+
+    let wanted = node
+        .files
+        .iter()
+        .filter(|index| stats[**index].wanted)
+        .count();
+    if wanted == node.files.len() {
+        "[x]"
+    } else if wanted == 0 {
+        "[ ]"
+    } else {
+        "[-]"
+    }
+}
+
+fn priority_marker(node: &FileNode, torrent: &Torrent) -> &'static str {
+    let stats = match torrent.file_stats.as_ref() {
+        Some(stats) => stats,
+        None => return "",
+    };
 
-        files_in_dir.push(item);
+    let mut priorities = node.files.iter().map(|index| stats[*index].priority);
+    let first = match priorities.next() {
+        Some(first) => first,
+        None => return "",
+    };
+    if priorities.any(|priority| priority != first) {
+        return " (mixed)";
     }
 
-    files_in_dir
+    match first {
+        1 => " (high)",
+        -1 => " (low)",
+        _ => "",
+    }
 }
@@ -1,9 +1,17 @@
 use crate::{
-    app::{FloatingWidget, RouteId},
+    add_form::AddRow,
+    app::{
+        AddSource, FilePriority, FloatingWidget, FocusableWidget, InfoTab, InputMode, PeerColumn,
+        RouteId,
+    },
     conversion::{
-        convert_bytes, convert_rate, convert_secs, date, get_ratio, get_status_percentage,
-        status_string,
+        convert_bytes, convert_rate, convert_secs, date, decode_pieces, get_ratio,
+        get_status_percentage, status_string,
     },
+    create::CreateRow,
+    filter::Category,
+    preview::PreviewLine,
+    tree::STATS_WIDTH,
 };
 
 use tui::{
@@ -13,8 +21,8 @@ use tui::{
     symbols,
     text::{Span, Spans, Text},
     widgets::{
-        Axis, Block, Borders, Chart, Clear, Dataset, List, ListItem, ListState, Paragraph, Row,
-        Table, TableState, Tabs,
+        Axis, Block, Borders, Cell, Chart, Clear, Dataset, Gauge, List, ListItem, ListState,
+        Paragraph, Row, Table, TableState, Tabs,
     },
     Frame,
 };
@@ -33,11 +41,32 @@ pub fn draw<B: Backend>(f: &mut Frame<B>, app: &mut App) {
 
     match app.floating_widget {
         FloatingWidget::Help => draw_help(f, app),
-        FloatingWidget::Input => draw_input(f, app),
+        FloatingWidget::Input => match app.last_route_focused_widget() {
+            Some(FocusableWidget::FileList) => draw_input(f, app, "Rename file"),
+            _ => draw_input(f, app, "Rename torrent"),
+        },
         FloatingWidget::AddTorrent => draw_add_torrent(f, app),
         FloatingWidget::AddTorrentConfirm => draw_add_torrent_confirm(f, app),
+        FloatingWidget::AddDuplicate => draw_add_duplicate(f, app),
+        FloatingWidget::AddPreview => draw_add_preview(f, app),
+        FloatingWidget::CreateSource => draw_add_torrent(f, app),
+        FloatingWidget::CreateTorrent => draw_create_torrent(f, app),
+        FloatingWidget::CreateProgress => draw_create_progress(f, app),
+        FloatingWidget::VerifyProgress => draw_verify_progress(f, app),
+        FloatingWidget::VerifyResults => draw_verify_results(f, app),
         FloatingWidget::RemoveTorrent => draw_delete_torrent(f, app),
+        FloatingWidget::RemoveTracker => draw_remove_tracker(f, app),
         FloatingWidget::ModifyColumns => draw_modify_columns(f, app),
+        FloatingWidget::Profiles => draw_profiles(f, app),
+        FloatingWidget::SavedFilters => draw_saved_filters(f, app),
+        FloatingWidget::TrackerInput if app.editing_tracker.is_some() => {
+            draw_input(f, app, "Replace tracker")
+        }
+        FloatingWidget::TrackerInput => draw_input(f, app, "Add tracker"),
+        FloatingWidget::AddLink => match &app.add_error {
+            Some(err) => draw_input(f, app, &format!("Add magnet link or URL [{}]", err)),
+            None => draw_input(f, app, "Add magnet link or URL (Ctrl+v to paste)"),
+        },
         _ => (),
     }
 }
@@ -55,11 +84,62 @@ fn draw_torrent_list<B: Backend>(f: &mut Frame<B>, app: &App) {
         )
         .split(f.size());
 
-    let block = Block::default().title("Torrents").borders(Borders::ALL);
+    let mut table_area = chunks[0];
+    if app.show_sidebar {
+        let sidebar_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(20), Constraint::Min(0)].as_ref())
+            .split(table_area);
+        draw_sidebar(f, app, sidebar_chunks[0]);
+        table_area = sidebar_chunks[1];
+    }
+
+    if let Some(banner) = app.banner() {
+        let banner_chunks = Layout::default()
+            .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+            .split(table_area);
+        let paragraph = Paragraph::new(banner)
+            .alignment(tui::layout::Alignment::Center)
+            .style(Style::default().fg(Color::Black).bg(Color::Red));
+        f.render_widget(paragraph, banner_chunks[0]);
+        table_area = banner_chunks[1];
+    }
+
+    let mut title = "Torrents".to_string();
+    let filtered =
+        !app.filter_pattern.is_empty() || matches!(app.floating_widget, FloatingWidget::Filter);
+    if filtered || app.category != Category::All {
+        title = format!(
+            "Torrents ({}/{})",
+            app.visible_torrents.len(),
+            app.torrents.len()
+        );
+    }
+    if filtered {
+        title.push_str(&format!(" /{}", app.filter_pattern));
+        if let FloatingWidget::Filter = app.floating_widget {
+            f.set_cursor(table_area.x + 1 + title.width() as u16, table_area.y);
+        }
+        if app.filter_regex {
+            title.push_str(" [regex]");
+        }
+        if let Some(err) = &app.filter_error {
+            title.push_str(&format!(" [{}]", err.lines().last().unwrap_or_default()));
+        }
+    }
+
+    let marked = app.marked_keys().len();
+    if app.visual_anchor.is_some() {
+        title.push_str(&format!(" [visual: {}]", marked));
+    } else if marked > 0 {
+        title.push_str(&format!(" [{} marked]", marked));
+    }
+
+    let block = Block::default().title(title).borders(Borders::ALL);
     let (header_rows, rows) = app.get_torrent_rows();
 
     let mut state = TableState::default();
-    state.select(app.selected_torrent);
+    state.select(app.selected_visible_index());
 
     let mut columns_count = 0;
     let mut widths = Vec::new();
@@ -80,57 +160,35 @@ fn draw_torrent_list<B: Backend>(f: &mut Frame<B>, app: &App) {
         .widths(&widths)
         .style(app.config.get_style())
         .highlight_style(app.config.get_highlight_style());
-    f.render_stateful_widget(table, chunks[0], &mut state);
+    f.render_stateful_widget(table, table_area, &mut state);
 
-    {
+    if let Some(session_stats) = app.get_session_stats() {
         let session_block = Block::default()
             .style(app.config.get_style())
             .borders(Borders::ALL)
             .title("Session Stats");
 
-        let table = Table::new(vec![
-            Row::new(vec![
-                "Down:".to_string(),
-                convert_rate(app.session_stats.as_ref().unwrap().download_speed),
-                "Up:".to_string(),
-                convert_rate(app.session_stats.as_ref().unwrap().upload_speed),
-                "Downloaded:".to_string(),
-                convert_bytes(
-                    app.session_stats
-                        .as_ref()
-                        .unwrap()
-                        .current_stats
-                        .downloaded_bytes,
-                ),
-                "Uploaded:".to_string(),
-                convert_bytes(
-                    app.session_stats
-                        .as_ref()
-                        .unwrap()
-                        .current_stats
-                        .uploaded_bytes,
-                ),
-            ]),
-            Row::new(vec![
-                "Slow Mode:".to_string(),
-                app.session.as_ref().unwrap().alt_speed_enabled.to_string(),
-                "Slow Mode Down:".to_string(),
-                convert_rate(app.session.as_ref().unwrap().alt_speed_down * 1000),
-                "Slow Mode Up:".to_string(),
-                convert_rate(app.session.as_ref().unwrap().alt_speed_up * 1000),
-            ]),
-        ])
-        .widths(&[
-            Constraint::Min(12),
-            Constraint::Min(10),
-            Constraint::Min(16),
-            Constraint::Min(14),
-            Constraint::Min(14),
-            Constraint::Min(14),
-            Constraint::Min(14),
-            Constraint::Min(14),
-        ])
-        .block(session_block);
+        let rows = session_stats.chunks(4).map(|chunk| {
+            let mut cells = Vec::new();
+            for (label, value) in chunk {
+                cells.push(format!("{}:", label));
+                cells.push(value.to_owned());
+            }
+            Row::new(cells)
+        });
+
+        let table = Table::new(rows)
+            .widths(&[
+                Constraint::Min(12),
+                Constraint::Min(10),
+                Constraint::Min(16),
+                Constraint::Min(14),
+                Constraint::Min(14),
+                Constraint::Min(14),
+                Constraint::Min(14),
+                Constraint::Min(14),
+            ])
+            .block(session_block);
         f.render_widget(table, chunks[2]);
     }
 
@@ -143,7 +201,7 @@ fn draw_torrent_list<B: Backend>(f: &mut Frame<B>, app: &App) {
         let info_block = Block::default().title("Information").borders(Borders::ALL);
         let transfer_block = Block::default().title("Transfer").borders(Borders::ALL);
 
-        if app.torrents.is_empty() {
+        if app.visible_torrents.is_empty() {
             return;
         }
 
@@ -260,26 +318,61 @@ fn draw_torrent_list<B: Backend>(f: &mut Frame<B>, app: &App) {
     }
 }
 
+fn draw_sidebar<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let items: Vec<ListItem> = Category::ALL
+        .iter()
+        .map(|category| {
+            ListItem::new(format!(
+                "{} ({})",
+                category.as_str(),
+                app.category_count(*category)
+            ))
+        })
+        .collect();
+
+    let highlight_style = if let Some(FocusableWidget::Sidebar) = app.last_route_focused_widget() {
+        app.config.get_highlight_style()
+    } else {
+        app.config.get_style().add_modifier(Modifier::BOLD)
+    };
+
+    let list = List::new(items)
+        .block(Block::default().title("Status").borders(Borders::ALL))
+        .style(app.config.get_style())
+        .highlight_style(highlight_style);
+
+    let mut state = ListState::default();
+    state.select(
+        Category::ALL
+            .iter()
+            .position(|category| *category == app.category),
+    );
+    f.render_stateful_widget(list, area, &mut state);
+}
+
 fn draw_torrent_info<B: Backend>(f: &mut Frame<B>, app: &mut App) {
-    let tabs = Tabs::new(vec![
-        Spans::from(Span::styled("Speed", Style::default())),
-        Spans::from(Span::styled("Files", Style::default())),
-        Spans::from(Span::styled("Logs", Style::default())),
-    ])
+    let tabs = Tabs::new(
+        InfoTab::ALL
+            .iter()
+            .map(|tab| Spans::from(Span::styled(tab.as_str(), Style::default())))
+            .collect(),
+    )
     .block(Block::default().borders(Borders::ALL).title("tabs"))
     .style(app.config.get_style())
     .highlight_style(app.config.get_style().fg(Color::Yellow))
-    .select(app.selected_tab);
+    .select(app.selected_tab_index());
     let chunks = Layout::default()
         .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
         .split(f.size());
     f.render_widget(tabs, chunks[0]);
 
     match app.selected_tab {
-        0 => draw_speed_chart(f, app, chunks[1]),
-        1 => draw_torrent_info_files(f, app, chunks[1]),
-        2 => logs(f, app, chunks[1]),
-        _ => (),
+        InfoTab::Speed => draw_speed_chart(f, app, chunks[1]),
+        InfoTab::Files => draw_torrent_info_files(f, app, chunks[1]),
+        InfoTab::Peers => draw_torrent_info_peers(f, app, chunks[1]),
+        InfoTab::Trackers => draw_torrent_info_trackers(f, app, chunks[1]),
+        InfoTab::Pieces => draw_torrent_info_pieces(f, app, chunks[1]),
+        InfoTab::Logs => logs(f, app, chunks[1]),
     }
 }
 
@@ -312,11 +405,185 @@ fn draw_speed_chart<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
 }
 
 fn draw_torrent_info_files<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
+    let block = Block::default()
+        .title("Files")
+        .borders(Borders::ALL)
+        .style(app.config.get_style());
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    // The item texts are padded to the width of the area
+    if app.tree.width != inner.width as usize {
+        app.tree.width = inner.width as usize;
+        app.refresh_tree();
+    }
+
+    let chunks = Layout::default()
+        .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    if inner.width as usize > STATS_WIDTH {
+        let header = Paragraph::new(format!("{:>10} {:>10} {:>6}", "Size", "Done", "%"))
+            .alignment(tui::layout::Alignment::Right)
+            .style(app.config.get_style().add_modifier(Modifier::BOLD));
+        f.render_widget(header, chunks[0]);
+    }
+
     let items = Tree::new(&*app.tree.items)
-        .block(Block::default().title("Files").borders(Borders::ALL))
         .style(app.config.get_style())
         .highlight_style(app.config.get_highlight_style());
-    f.render_stateful_widget(items, area, &mut app.tree.state);
+    f.render_stateful_widget(items, chunks[1], &mut app.tree.state);
+}
+
+fn draw_torrent_info_peers<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let header: Vec<String> = PeerColumn::ALL
+        .iter()
+        .map(|column| {
+            if *column != app.peer_sort_column {
+                column.as_str().to_string()
+            } else if app.peer_sort_descending {
+                format!("{} ▼", column.as_str())
+            } else {
+                format!("{} ▲", column.as_str())
+            }
+        })
+        .collect();
+    let rows = app.get_peer_rows();
+    let title = format!("Peers ({})", rows.len());
+
+    let mut state = TableState::default();
+    if let Some(FocusableWidget::PeerList) = app.last_route_focused_widget() {
+        state.select(app.selected_peer.filter(|index| *index < rows.len()));
+    }
+
+    let table = Table::new(rows.into_iter().map(Row::new))
+        .header(Row::new(header).style(Style::default().add_modifier(Modifier::BOLD)))
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .widths(&[
+            Constraint::Percentage(20),
+            Constraint::Percentage(18),
+            Constraint::Percentage(10),
+            Constraint::Percentage(10),
+            Constraint::Percentage(11),
+            Constraint::Percentage(11),
+            Constraint::Percentage(9),
+            Constraint::Percentage(11),
+        ])
+        .style(app.config.get_style())
+        .highlight_style(app.config.get_highlight_style());
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+fn draw_torrent_info_trackers<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let header = vec![
+        "Tier",
+        "Announce URL",
+        "Last Announce",
+        "Announce Result",
+        "Last Scrape",
+        "Scrape Result",
+        "Seeders",
+        "Leechers",
+        "Next Announce",
+    ];
+    let rows: Vec<Row> = app
+        .get_tracker_rows()
+        .into_iter()
+        .map(|(failed, row)| {
+            if failed {
+                Row::new(row).style(Style::default().fg(Color::Red))
+            } else {
+                Row::new(row)
+            }
+        })
+        .collect();
+    let title = format!("Trackers ({})", rows.len());
+
+    let mut state = TableState::default();
+    if let Some(FocusableWidget::TrackerList) = app.last_route_focused_widget() {
+        state.select(app.selected_tracker.filter(|index| *index < rows.len()));
+    }
+
+    let table = Table::new(rows)
+        .header(Row::new(header).style(Style::default().add_modifier(Modifier::BOLD)))
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .widths(&[
+            Constraint::Length(4),
+            Constraint::Percentage(25),
+            Constraint::Length(16),
+            Constraint::Percentage(15),
+            Constraint::Length(16),
+            Constraint::Percentage(15),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(16),
+        ])
+        .style(app.config.get_style())
+        .highlight_style(app.config.get_highlight_style());
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+const PARTIAL_BLOCKS: [&str; 7] = ["▁", "▂", "▃", "▄", "▅", "▆", "▇"];
+
+// Each cell stands for a run of pieces: full if all of them are downloaded,
+// shaded if none are and a partial block otherwise
+fn draw_torrent_info_pieces<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let torrent = match app
+        .selected_torrent
+        .and_then(|index| app.torrents.get(index))
+    {
+        Some(torrent) => torrent,
+        None => return,
+    };
+
+    let count = torrent.piece_count.unwrap_or(0) as usize;
+    let pieces = decode_pieces(torrent.pieces.as_deref().unwrap_or_default(), count);
+    let have = pieces.iter().filter(|piece| **piece).count();
+    let title = format!(
+        "Pieces ({}/{}, {} each)",
+        have,
+        count,
+        convert_bytes(torrent.piece_size.unwrap_or(0))
+    );
+    let block = Block::default().title(title).borders(Borders::ALL);
+
+    let inner = block.inner(area);
+    let width = inner.width as usize;
+    let cells = (width * inner.height as usize).min(count);
+
+    let mut lines = Vec::new();
+    let mut spans = Vec::new();
+    for cell in 0..cells {
+        let start = cell * count / cells;
+        let end = (cell + 1) * count / cells;
+        let have = pieces[start..end].iter().filter(|piece| **piece).count();
+
+        let span = if have == end - start {
+            Span::styled("█", Style::default().fg(Color::LightGreen))
+        } else if have == 0 {
+            Span::styled("░", Style::default().fg(Color::DarkGray))
+        } else {
+            Span::styled(
+                PARTIAL_BLOCKS[have * PARTIAL_BLOCKS.len() / (end - start)],
+                Style::default().fg(Color::Yellow),
+            )
+        };
+        spans.push(span);
+
+        if spans.len() == width {
+            lines.push(Spans::from(std::mem::take(&mut spans)));
+        }
+    }
+    if !spans.is_empty() {
+        lines.push(Spans::from(spans));
+    }
+
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .style(app.config.get_style()),
+        area,
+    );
 }
 
 fn logs<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
@@ -328,17 +595,26 @@ fn logs<B: Backend>(f: &mut Frame<B>, app: &mut App, area: Rect) {
 
 fn draw_help<B: Backend>(f: &mut Frame<B>, app: &App) {
     let block = Block::default().title("Help").borders(Borders::ALL);
-    let area = floating_rect(f, 50, 15);
+    let area = floating_rect(f, 50, 24);
     let rows = vec![
         Row::new(vec!["j / Down", "Move down"]),
         Row::new(vec!["k / Up", "Move up"]),
         Row::new(vec!["l", "Open torrent / Move right"]),
         Row::new(vec!["h", "Move left"]),
+        Row::new(vec!["Space", "Mark torrent"]),
+        Row::new(vec!["V", "Mark a range of torrents"]),
+        Row::new(vec!["Ctrl+a", "Mark all filtered torrents"]),
         Row::new(vec!["p", "Pause/unpause torrent"]),
         Row::new(vec!["r", "Rename torrent"]),
         Row::new(vec!["d", "Delete torrent"]),
         Row::new(vec!["t", "Toggle torrent files deletion"]),
         Row::new(vec!["c", "Modify torrent field columns"]),
+        Row::new(vec!["P", "Switch daemon profile"]),
+        Row::new(vec!["/", "Filter torrents, Ctrl+r toggles regex"]),
+        Row::new(vec!["f", "Apply a saved filter"]),
+        Row::new(vec!["s", "Toggle status sidebar, h to focus it"]),
+        Row::new(vec!["m", "Add magnet link or URL"]),
+        Row::new(vec!["n", "Create a new torrent"]),
         Row::new(vec!["Enter", "Confirm"]),
         Row::new(vec!["Esc", "Go back"]),
         Row::new(vec!["q", "Exit"]),
@@ -351,13 +627,13 @@ fn draw_help<B: Backend>(f: &mut Frame<B>, app: &App) {
     f.render_widget(table, area);
 }
 
-fn draw_input<B: Backend>(f: &mut Frame<B>, app: &App) {
+fn draw_input<B: Backend>(f: &mut Frame<B>, app: &App, title: &str) {
     let area = floating_rect(f, 100, 3);
     let input = Paragraph::new(app.input.as_ref()).block(
         Block::default()
             .borders(Borders::ALL)
             .style(app.config.get_style())
-            .title("Rename torrent"),
+            .title(title),
     );
 
     f.set_cursor(area.x + app.input.width() as u16 + 1, area.y + 1);
@@ -366,62 +642,497 @@ fn draw_input<B: Backend>(f: &mut Frame<B>, app: &App) {
 }
 
 fn draw_add_torrent<B: Backend>(f: &mut Frame<B>, app: &App) {
-    let area = floating_rect(f, 100, app.torrent_files.len() as u32 + 2);
+    let browser = &app.browser;
+    let area = floating_rect(f, 100, 20);
+    let chunks = Layout::default()
+        .constraints([Constraint::Min(1), Constraint::Length(2)])
+        .split(area);
+
+    let mut title = browser.dir.to_string_lossy().to_string();
+    if !browser.filter.is_empty() {
+        title.push_str(&format!(" /{}", browser.filter));
+    }
+    if let Some(err) = &browser.error {
+        title.push_str(&format!(" [{}]", err));
+    }
+
     let mut rows = Vec::new();
-    for file in &app.torrent_files {
-        rows.push(ListItem::new(file.to_str().unwrap()));
+    for index in &browser.visible {
+        let entry = &browser.entries[*index];
+        if entry.is_dir {
+            rows.push(ListItem::new(format!("{}/", entry.name)));
+        } else {
+            rows.push(ListItem::new(entry.name.to_owned()));
+        }
     }
     let list = List::new(rows)
-        .block(Block::default().borders(Borders::ALL).title("Add torrent"))
+        .block(
+            Block::default()
+                .borders(Borders::LEFT | Borders::TOP | Borders::RIGHT)
+                .title(title),
+        )
         .style(app.config.get_style())
         .highlight_style(app.config.get_highlight_style());
 
     let mut state = ListState::default();
-    state.select(app.selected_torrent_file);
+    state.select(browser.selected);
+
+    let mut hints = Vec::new();
+    for (i, (name, _)) in app.config.bookmarks.iter().take(9).enumerate() {
+        hints.push(format!("{} {}", i + 1, name));
+    }
+    hints.push(". hidden".to_string());
+    hints.push("/ filter".to_string());
+    if let FloatingWidget::CreateSource = app.floating_widget {
+        hints.push("Space pick directory".to_string());
+    }
+    let hints = Paragraph::new(hints.join("  "))
+        .block(Block::default().borders(Borders::LEFT | Borders::BOTTOM | Borders::RIGHT))
+        .style(app.config.get_style());
 
     f.render_widget(Clear, area);
-    f.render_stateful_widget(list, area, &mut state);
+    f.render_stateful_widget(list, chunks[0], &mut state);
+    f.render_widget(hints, chunks[1]);
 }
 
 fn draw_add_torrent_confirm<B: Backend>(f: &mut Frame<B>, app: &App) {
-    let torrent = lava_torrent::torrent::v1::Torrent::read_from_file(
-        app.torrent_files[app.selected_torrent_file.unwrap()].as_path(),
-    )
-    .unwrap();
-
-    let text = Text::from(Spans::from(vec![
-        Span::raw("Press "),
-        Span::styled("P", Style::default().add_modifier(Modifier::BOLD)),
-        Span::raw(" to toggle paused, "),
-        Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
-        Span::raw(" to add torrent"),
-    ]));
+    let mut rows = match &app.add_source {
+        Some(AddSource::File(path)) => {
+            let mut rows = vec![Row::new(vec![
+                "Filename".to_string(),
+                path.file_name().unwrap().to_string_lossy().to_string(),
+            ])];
+            match &app.add_preview {
+                Some(preview) => rows.extend([
+                    Row::new(vec!["Torrent name".to_string(), preview.name.to_owned()]),
+                    Row::new(vec!["Size".to_string(), convert_bytes(preview.size)]),
+                    Row::new(vec!["Info hash".to_string(), preview.info_hash.to_owned()]),
+                ]),
+                None => rows.push(Row::new(vec![
+                    "Torrent name".to_string(),
+                    app.preview_error.to_owned().unwrap_or_default(),
+                ])),
+            }
+            rows
+        }
+        Some(AddSource::Magnet(magnet)) => {
+            let mut rows = vec![
+                Row::new(vec![
+                    "Torrent name".to_string(),
+                    magnet
+                        .name
+                        .to_owned()
+                        .unwrap_or_else(|| "Unknown".to_string()),
+                ]),
+                Row::new(vec!["Info hash".to_string(), magnet.hash.to_owned()]),
+            ];
+            for (i, tracker) in magnet.trackers.iter().enumerate() {
+                let label = if i == 0 { "Trackers" } else { "" };
+                rows.push(Row::new(vec![label.to_string(), tracker.to_owned()]));
+            }
+            rows
+        }
+        Some(AddSource::Url(url)) => vec![Row::new(vec!["URL".to_string(), url.to_owned()])],
+        None => Vec::new(),
+    };
+    if let Some(daemon) = app.add_target() {
+        rows.push(Row::new(vec!["Add to".to_string(), daemon.to_string()]));
+    }
+    let info_height = rows.len() as u16;
+
+    let form = &app.add_form;
+    let mut form_rows = vec![
+        Row::new(vec![
+            "Download dir".to_string(),
+            form.download_dir.to_owned(),
+        ]),
+        Row::new(vec![
+            "Priority".to_string(),
+            form.priority.as_str().to_string(),
+        ]),
+        Row::new(vec![
+            "Peer limit".to_string(),
+            form.peer_limit
+                .map(|limit| limit.to_string())
+                .unwrap_or_else(|| "Default".to_string()),
+        ]),
+        Row::new(vec!["Start paused".to_string(), form.paused.to_string()]),
+    ];
+    for (i, file) in form.files.iter().enumerate() {
+        let label = if i == 0 { "Files" } else { "" };
+        let wanted = if file.wanted { "[x]" } else { "[ ]" };
+        let priority = match file.priority {
+            FilePriority::Normal => String::new(),
+            priority => format!(" ({})", priority.as_str().to_lowercase()),
+        };
+        form_rows.push(Row::new(vec![
+            label.to_string(),
+            format!(
+                "{} {} {}{}",
+                wanted,
+                file.name,
+                convert_bytes(file.length),
+                priority
+            ),
+        ]));
+    }
+
+    // The top line doubles as the prompt while a field is edited
+    let prompt = match form.row() {
+        AddRow::DownloadDir => "Download dir (Tab completes): ",
+        _ => "Peer limit: ",
+    };
+    let text = match app.input_mode {
+        InputMode::Editing => Text::from(format!("{}{}", prompt, app.input)),
+        InputMode::Normal => Text::from(Spans::from(vec![
+            Span::raw("Press "),
+            Span::styled("Space", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to change, "),
+            Span::styled("+ = -", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" for priority, "),
+            Span::styled("i", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" for details, "),
+            Span::styled("v", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to verify local data, "),
+            Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to add torrent"),
+        ])),
+    };
+
+    let height = info_height as u32 + form_rows.len() as u32 + 4;
+    let area = floating_rect(f, 100, height.min(f.size().height as u32));
+    let chunks = Layout::default()
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(info_height),
+            Constraint::Min(1),
+        ])
+        .margin(1)
+        .split(area);
+    let table = Table::new(rows)
+        .style(app.config.get_style())
+        .widths(&[Constraint::Percentage(30), Constraint::Percentage(70)]);
+    let form_table = Table::new(form_rows)
+        .style(app.config.get_style())
+        .highlight_style(app.config.get_highlight_style())
+        .widths(&[Constraint::Percentage(30), Constraint::Percentage(70)]);
+    let title = match &app.add_error {
+        Some(err) => format!("Add torrent [{}]", err),
+        None => "Add torrent".to_string(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .style(app.config.get_style());
+
+    let mut state = TableState::default();
+    state.select(Some(form.selected));
+
+    f.render_widget(Clear, area);
+    match app.input_mode {
+        InputMode::Editing => {
+            f.set_cursor(
+                chunks[0].x + prompt.width() as u16 + app.input.width() as u16,
+                chunks[0].y,
+            );
+            f.render_widget(
+                Paragraph::new(text).style(app.config.get_style()),
+                chunks[0],
+            );
+        }
+        InputMode::Normal => f.render_widget(
+            Paragraph::new(text)
+                .alignment(tui::layout::Alignment::Center)
+                .style(app.config.get_style()),
+            chunks[0],
+        ),
+    }
+    f.render_widget(table, chunks[1]);
+    f.render_stateful_widget(form_table, chunks[2], &mut state);
+    f.render_widget(block, area);
+}
+
+fn draw_create_torrent<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let form = match &app.create_form {
+        Some(form) => form,
+        None => return,
+    };
+
+    let mut rows = vec![
+        Row::new(vec![
+            "Content".to_string(),
+            form.path.to_string_lossy().to_string(),
+        ]),
+        Row::new(vec![
+            "Size".to_string(),
+            match form.size {
+                Some(size) => convert_bytes(size),
+                None => "Counting...".to_string(),
+            },
+        ]),
+    ];
+    for row in CreateRow::ALL {
+        rows.push(Row::new(vec![row.as_str().to_string(), form.value(row)]));
+    }
+
+    // The top line doubles as the prompt while a field is edited
+    let prompt = match form.row() {
+        CreateRow::Trackers => "Trackers, tiers separated by spaces: ".to_string(),
+        CreateRow::PieceSize => "Piece size in KiB, empty for auto: ".to_string(),
+        row => format!("{}: ", row.as_str()),
+    };
+    let text = match app.input_mode {
+        InputMode::Editing => Text::from(format!("{}{}", prompt, app.input)),
+        InputMode::Normal => Text::from(Spans::from(vec![
+            Span::raw("Press "),
+            Span::styled("Space", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to change, "),
+            Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to create torrent"),
+        ])),
+    };
 
-    let area = floating_rect(f, 100, 9);
+    let area = floating_rect(f, 100, rows.len() as u32 + 4);
     let chunks = Layout::default()
         .constraints([Constraint::Length(2), Constraint::Min(1)])
         .margin(1)
         .split(area);
-    let rows = vec![
+    let table = Table::new(rows)
+        .style(app.config.get_style())
+        .highlight_style(app.config.get_highlight_style())
+        .widths(&[Constraint::Percentage(30), Constraint::Percentage(70)]);
+    let title = match &form.error {
+        Some(err) => format!("Create torrent [{}]", err),
+        None => "Create torrent".to_string(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .style(app.config.get_style());
+
+    // The content and size rows come before the fields
+    let mut state = TableState::default();
+    state.select(Some(form.selected + 2));
+
+    f.render_widget(Clear, area);
+    match app.input_mode {
+        InputMode::Editing => {
+            f.set_cursor(
+                chunks[0].x + prompt.width() as u16 + app.input.width() as u16,
+                chunks[0].y,
+            );
+            f.render_widget(
+                Paragraph::new(text).style(app.config.get_style()),
+                chunks[0],
+            );
+        }
+        InputMode::Normal => f.render_widget(
+            Paragraph::new(text)
+                .alignment(tui::layout::Alignment::Center)
+                .style(app.config.get_style()),
+            chunks[0],
+        ),
+    }
+    f.render_stateful_widget(table, chunks[1], &mut state);
+    f.render_widget(block, area);
+}
+
+fn draw_create_progress<B: Backend>(f: &mut Frame<B>, app: &App) {
+    if let Some(job) = &app.create_job {
+        let (processed, total) = job.progress();
+        let title = format!("Hashing {}", job.output.display());
+        draw_progress(f, &title, processed, total);
+    }
+}
+
+fn draw_verify_progress<B: Backend>(f: &mut Frame<B>, app: &App) {
+    if let Some(job) = &app.verify_job {
+        let (processed, total) = job.progress();
+        let title = format!("Verifying {}", job.name);
+        draw_progress(f, &title, processed as u64, total as u64);
+    }
+}
+
+fn draw_progress<B: Backend>(f: &mut Frame<B>, title: &str, processed: u64, total: u64) {
+    let ratio = if total == 0 {
+        0.0
+    } else {
+        processed as f64 / total as f64
+    };
+
+    let area = floating_rect(f, 60, 3);
+    let gauge = Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .gauge_style(Style::default().fg(Color::Green))
+        .label(format!("{} / {} pieces", processed, total))
+        .ratio(ratio.min(1.0));
+
+    f.render_widget(Clear, area);
+    f.render_widget(gauge, area);
+}
+
+fn draw_verify_results<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let report = match &app.verify_report {
+        Some(report) => report,
+        None => return,
+    };
+
+    let good = report.piece_count - report.bad_pieces.len();
+    let mut text = Text::from(format!(
+        "{} of {} pieces match the data in {}",
+        good,
+        report.piece_count,
+        report.dir.display()
+    ));
+    if !report.bad_pieces.is_empty() {
+        text.extend(Text::styled(
+            format!("Bad pieces: {}", report.bad_ranges()),
+            Style::default().fg(Color::Red),
+        ));
+    }
+
+    let mut rows = Vec::new();
+    for file in &report.files {
+        let color = if file.is_ok() {
+            Color::Green
+        } else {
+            Color::Red
+        };
+        rows.push(Row::new(vec![
+            Cell::from(file.name.to_owned()),
+            Cell::from(convert_bytes(file.length)),
+            Cell::from(file.status()).style(Style::default().fg(color)),
+        ]));
+    }
+
+    let height = (rows.len() as u32 + 6).min(f.size().height.saturating_sub(2) as u32);
+    let area = floating_rect(f, 100, height);
+    let chunks = Layout::default()
+        .constraints([Constraint::Length(2), Constraint::Min(1)])
+        .margin(1)
+        .split(area);
+    let table = Table::new(rows)
+        .header(
+            Row::new(vec!["Name", "Size", "Status"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .style(app.config.get_style())
+        .highlight_style(app.config.get_highlight_style())
+        .widths(&[
+            Constraint::Percentage(60),
+            Constraint::Percentage(15),
+            Constraint::Percentage(25),
+        ]);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "Local verification of {} (h to go back)",
+            report.name
+        ))
+        .style(app.config.get_style());
+
+    let mut state = TableState::default();
+    state.select(app.selected_verify_file);
+
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(text).style(app.config.get_style()),
+        chunks[0],
+    );
+    f.render_stateful_widget(table, chunks[1], &mut state);
+    f.render_widget(block, area);
+}
+
+fn draw_add_preview<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let preview = match &app.add_preview {
+        Some(preview) => preview,
+        None => return,
+    };
+
+    let height = (preview.lines.len() + 2).min(f.size().height.saturating_sub(2) as usize) as u32;
+    let area = floating_rect(f, 100, height);
+    let width = area.width.saturating_sub(2) as usize;
+
+    let mut lines = Vec::new();
+    for line in &preview.lines {
+        lines.push(match line {
+            PreviewLine::Section(title) => Spans::from(Span::styled(
+                title.to_owned(),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            PreviewLine::Field(label, value) => Spans::from(format!("  {:<12}{}", label, value)),
+            PreviewLine::Item(text) => Spans::from(format!("  {}", text)),
+            // Sizes are right aligned, like in the Files tab
+            PreviewLine::File { depth, name, size } => {
+                let name = format!("  {}{}", "  ".repeat(*depth), name);
+                let size = convert_bytes(*size);
+                let padding = width.saturating_sub(name.width() + size.width()).max(1);
+                Spans::from(format!("{}{}{}", name, " ".repeat(padding), size))
+            }
+        });
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Torrent details (h to go back)"),
+        )
+        .style(app.config.get_style())
+        .scroll((app.preview_scroll, 0));
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
+
+fn draw_add_duplicate<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let duplicate = match &app.add_duplicate {
+        Some(duplicate) => duplicate,
+        None => return,
+    };
+
+    let mut rows = vec![
+        Row::new(vec!["Torrent name".to_string(), duplicate.name.to_owned()]),
         Row::new(vec![
-            "Filename",
-            app.torrent_files[app.selected_torrent_file.unwrap()]
-                .file_name()
-                .unwrap()
-                .to_str()
-                .unwrap(),
+            "Status".to_string(),
+            status_string(&duplicate.status).to_string(),
         ]),
-        Row::new(vec!["Torrent name".to_string(), torrent.name.to_string()]),
-        Row::new(vec!["Size".to_string(), convert_bytes(torrent.length)]),
-        Row::new(vec!["Info hash".to_string(), torrent.info_hash()]),
-        Row::new(vec!["Start paused".to_string(), app.add_paused.to_string()]),
+        Row::new(vec!["Info hash".to_string(), duplicate.hash.to_owned()]),
     ];
+    if let Some(daemon) = app.add_target() {
+        rows.push(Row::new(vec!["Daemon".to_string(), daemon.to_string()]));
+    }
+    for (i, tracker) in duplicate.new_trackers.iter().enumerate() {
+        let label = if i == 0 { "New trackers" } else { "" };
+        rows.push(Row::new(vec![label.to_string(), tracker.to_owned()]));
+    }
+
+    let text = if duplicate.new_trackers.is_empty() {
+        Text::from("The torrent already has all of these trackers")
+    } else {
+        Text::from(Spans::from(vec![
+            Span::raw("Press "),
+            Span::styled("Enter", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to add the new trackers to it, "),
+            Span::styled("Esc", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to cancel"),
+        ]))
+    };
+
+    let area = floating_rect(f, 100, rows.len() as u32 + 4);
+    let chunks = Layout::default()
+        .constraints([Constraint::Length(2), Constraint::Min(1)])
+        .margin(1)
+        .split(area);
     let table = Table::new(rows)
         .style(app.config.get_style())
         .widths(&[Constraint::Percentage(30), Constraint::Percentage(70)]);
     let block = Block::default()
         .borders(Borders::ALL)
-        .title("Add torrent")
+        .title(Span::styled(
+            "Torrent already added",
+            Style::default().fg(Color::Red),
+        ))
         .style(app.config.get_style());
 
     f.render_widget(Clear, area);
@@ -437,12 +1148,14 @@ fn draw_add_torrent_confirm<B: Backend>(f: &mut Frame<B>, app: &App) {
 
 fn draw_delete_torrent<B: Backend>(f: &mut Frame<B>, app: &App) {
     let area = floating_rect(f, 45, 6);
+    let name = match app.marked_keys().len() {
+        0 => app.get_selected_torrent_name(),
+        1 => "1 marked torrent".to_string(),
+        count => format!("{} marked torrents", count),
+    };
     let mut text = Text::from(Spans::from(vec![
         Span::from("Delete "),
-        Span::styled(
-            app.get_selected_torrent_name(),
-            Style::default().add_modifier(Modifier::ITALIC),
-        ),
+        Span::styled(name, Style::default().add_modifier(Modifier::ITALIC)),
         Span::from("?"),
     ]));
 
@@ -473,6 +1186,32 @@ fn draw_delete_torrent<B: Backend>(f: &mut Frame<B>, app: &App) {
     );
 }
 
+fn draw_remove_tracker<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let announce = match app.selected_tracker_stat() {
+        Some(stat) => stat.announce.to_owned(),
+        None => return,
+    };
+    let area = floating_rect(f, 60, 5);
+    let mut text = Text::from(Spans::from(vec![
+        Span::from("Remove "),
+        Span::styled(announce, Style::default().add_modifier(Modifier::ITALIC)),
+        Span::from("?"),
+    ]));
+    text.extend(Text::raw("\nPress Enter to remove, Esc to cancel"));
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Remove tracker");
+
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(text)
+            .block(block)
+            .alignment(tui::layout::Alignment::Center)
+            .style(app.config.get_style()),
+        area,
+    );
+}
+
 fn draw_modify_columns<B: Backend>(f: &mut Frame<B>, app: &App) {
     let area = floating_rect(f, 40, 15);
     let chunks = Layout::default()
@@ -521,6 +1260,60 @@ fn draw_modify_columns<B: Backend>(f: &mut Frame<B>, app: &App) {
     f.render_stateful_widget(list, chunks[1], &mut state);
 }
 
+fn draw_profiles<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let area = floating_rect(f, 60, app.config.profiles.len() as u32 + 3);
+    let mut rows = Vec::new();
+    for (i, profile) in app.config.profiles.iter().enumerate() {
+        let marker = if !app.aggregated && i == app.active_profile {
+            "* "
+        } else {
+            "  "
+        };
+        rows.push(ListItem::new(format!(
+            "{}{} ({})",
+            marker,
+            profile.name,
+            profile.connection.url()
+        )));
+    }
+    let marker = if app.aggregated { "* " } else { "  " };
+    rows.push(ListItem::new(format!("{}All daemons (aggregated)", marker)));
+    let list = List::new(rows)
+        .block(Block::default().borders(Borders::ALL).title("Profiles"))
+        .style(app.config.get_style())
+        .highlight_style(app.config.get_highlight_style());
+
+    let mut state = ListState::default();
+    state.select(app.selected_profile);
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_saved_filters<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let area = floating_rect(f, 60, app.config.filters.len() as u32 + 2);
+    let rows = app
+        .config
+        .filters
+        .iter()
+        .map(|(name, query)| ListItem::new(format!("{}: {}", name, query)))
+        .collect::<Vec<_>>();
+    let list = List::new(rows)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Saved filters"),
+        )
+        .style(app.config.get_style())
+        .highlight_style(app.config.get_highlight_style());
+
+    let mut state = ListState::default();
+    state.select(app.selected_saved_filter);
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut state);
+}
+
 fn floating_rect<B: Backend>(f: &mut Frame<B>, width: u32, height: u32) -> Rect {
     let float_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -0,0 +1,389 @@
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use lava_torrent::{
+    bencode::BencodeElem,
+    torrent::v1::{self, Torrent},
+};
+use sha1::{Digest, Sha1};
+
+pub struct FileResult {
+    pub name: String,
+    pub length: i64,
+    pub size_on_disk: Option<u64>,
+    pub pieces: usize,
+    pub bad_pieces: usize,
+}
+
+impl FileResult {
+    pub fn is_ok(&self) -> bool {
+        self.size_on_disk == Some(self.length as u64) && self.bad_pieces == 0
+    }
+
+    pub fn status(&self) -> String {
+        match self.size_on_disk {
+            None => "Missing".to_string(),
+            Some(size) if size != self.length as u64 => format!("Wrong size ({} bytes)", size),
+            Some(_) if self.bad_pieces == 0 => "Ok".to_string(),
+            Some(_) => format!("{} of {} pieces bad", self.bad_pieces, self.pieces),
+        }
+    }
+}
+
+pub struct VerifyReport {
+    pub name: String,
+    pub dir: PathBuf,
+    pub files: Vec<FileResult>,
+    pub piece_count: usize,
+    pub bad_pieces: Vec<usize>,
+}
+
+impl VerifyReport {
+    // Consecutive bad pieces are shown as ranges, "3-7, 12"
+    pub fn bad_ranges(&self) -> String {
+        let mut ranges: Vec<(usize, usize)> = Vec::new();
+        for piece in &self.bad_pieces {
+            match ranges.last_mut() {
+                Some((_, end)) if *end + 1 == *piece => *end = *piece,
+                _ => ranges.push((*piece, *piece)),
+            }
+        }
+
+        ranges
+            .into_iter()
+            .map(|(start, end)| {
+                if start == end {
+                    start.to_string()
+                } else {
+                    format!("{}-{}", start, end)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+// Hashes the data in a thread of its own, like Transmission's verify but
+// without the daemon, so data can be checked before it's added
+pub struct VerifyJob {
+    pub name: String,
+    pub piece_count: usize,
+    processed: Arc<AtomicUsize>,
+    handle: JoinHandle<VerifyReport>,
+}
+
+impl VerifyJob {
+    pub fn start(torrent: Torrent, dir: PathBuf) -> VerifyJob {
+        let processed = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&processed);
+        let name = torrent.name.to_owned();
+        let piece_count = torrent.pieces.len();
+
+        VerifyJob {
+            name,
+            piece_count,
+            processed,
+            handle: thread::spawn(move || verify(&torrent, &dir, &counter)),
+        }
+    }
+
+    pub fn progress(&self) -> (usize, usize) {
+        (self.processed.load(Ordering::Relaxed), self.piece_count)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    pub fn report(self) -> Option<VerifyReport> {
+        self.handle.join().ok()
+    }
+}
+
+struct Source {
+    name: String,
+    path: PathBuf,
+    length: i64,
+    padding: bool,
+}
+
+// Multi file torrents keep their files in a directory named after the torrent
+fn torrent_files(torrent: &Torrent, dir: &Path) -> Vec<Source> {
+    match &torrent.files {
+        Some(files) => files
+            .iter()
+            .map(|file| Source {
+                name: file.path.to_string_lossy().to_string(),
+                path: dir.join(&torrent.name).join(&file.path),
+                length: file.length,
+                padding: is_padding(file),
+            })
+            .collect(),
+        None => vec![Source {
+            name: torrent.name.to_owned(),
+            path: dir.join(&torrent.name),
+            length: torrent.length,
+            padding: false,
+        }],
+    }
+}
+
+// BEP 47 padding files of hybrid torrents are never written to disk, their
+// data is all zeros
+fn is_padding(file: &v1::File) -> bool {
+    let attr = file
+        .extra_fields
+        .as_ref()
+        .and_then(|fields| fields.get("attr"));
+    match attr {
+        Some(BencodeElem::String(attr)) => attr.contains('p'),
+        Some(BencodeElem::Bytes(attr)) => attr.contains(&b'p'),
+        _ => file.path.starts_with(".pad"),
+    }
+}
+
+struct Hasher<'a> {
+    torrent: &'a Torrent,
+    piece: Vec<u8>,
+    // The piece is known to be bad when a file is missing or too short
+    broken: bool,
+    touched: Vec<usize>,
+    index: usize,
+    bad_pieces: Vec<usize>,
+}
+
+impl Hasher<'_> {
+    fn finish_piece(&mut self, files: &mut [FileResult], processed: &AtomicUsize) {
+        let good = !self.broken
+            && matches!(
+                self.torrent.pieces.get(self.index),
+                Some(hash) if Sha1::digest(&self.piece)[..] == hash[..]
+            );
+
+        for file in &self.touched {
+            files[*file].pieces += 1;
+            if !good {
+                files[*file].bad_pieces += 1;
+            }
+        }
+        if !good {
+            self.bad_pieces.push(self.index);
+        }
+
+        self.index += 1;
+        processed.store(self.index, Ordering::Relaxed);
+        self.piece.clear();
+        self.touched.clear();
+        self.broken = false;
+    }
+}
+
+fn verify(torrent: &Torrent, dir: &Path, processed: &AtomicUsize) -> VerifyReport {
+    let piece_length = torrent.piece_length as usize;
+    let sources = torrent_files(torrent, dir);
+    let mut files: Vec<FileResult> = sources
+        .iter()
+        .filter(|source| !source.padding)
+        .map(|source| FileResult {
+            name: source.name.to_owned(),
+            length: source.length,
+            size_on_disk: source.path.metadata().ok().map(|metadata| metadata.len()),
+            pieces: 0,
+            bad_pieces: 0,
+        })
+        .collect();
+
+    let mut hasher = Hasher {
+        torrent,
+        piece: Vec::with_capacity(piece_length),
+        broken: false,
+        touched: Vec::new(),
+        index: 0,
+        bad_pieces: Vec::new(),
+    };
+
+    let mut file = 0;
+    for source in &sources {
+        let mut reader = if source.padding {
+            None
+        } else {
+            File::open(&source.path).ok()
+        };
+        let mut remaining = source.length as usize;
+
+        while remaining > 0 {
+            let start = hasher.piece.len();
+            let want = remaining.min(piece_length - start);
+            // Padding stays zeroed
+            hasher.piece.resize(start + want, 0);
+            if !source.padding {
+                let read = match reader.as_mut() {
+                    Some(reader) => reader.read_exact(&mut hasher.piece[start..]).is_ok(),
+                    None => false,
+                };
+                if !read {
+                    hasher.broken = true;
+                    reader = None;
+                }
+                hasher.touched.push(file);
+            }
+            remaining -= want;
+
+            if hasher.piece.len() == piece_length {
+                hasher.finish_piece(&mut files, processed);
+            }
+        }
+
+        if !source.padding {
+            file += 1;
+        }
+    }
+    if !hasher.piece.is_empty() {
+        hasher.finish_piece(&mut files, processed);
+    }
+
+    VerifyReport {
+        name: torrent.name.to_owned(),
+        dir: dir.to_path_buf(),
+        files,
+        piece_count: torrent.pieces.len(),
+        bad_pieces: hasher.bad_pieces,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fs};
+
+    use super::*;
+
+    const PIECE_LENGTH: usize = 4;
+
+    // A multi file torrent of `files`, hashed from their contents
+    fn torrent(name: &str, files: &[(&str, &[u8])]) -> Torrent {
+        let data: Vec<u8> = files.iter().flat_map(|(_, data)| data.to_vec()).collect();
+        let files = files
+            .iter()
+            .map(|(path, data)| v1::File {
+                length: data.len() as i64,
+                path: PathBuf::from(path),
+                extra_fields: if path.starts_with(".pad/") {
+                    Some(HashMap::from([(
+                        "attr".to_string(),
+                        BencodeElem::String("p".to_string()),
+                    )]))
+                } else {
+                    None
+                },
+            })
+            .collect();
+
+        Torrent {
+            announce: None,
+            announce_list: None,
+            length: data.len() as i64,
+            files: Some(files),
+            name: name.to_string(),
+            piece_length: PIECE_LENGTH as i64,
+            pieces: data
+                .chunks(PIECE_LENGTH)
+                .map(|piece| Sha1::digest(piece).to_vec())
+                .collect(),
+            extra_fields: None,
+            extra_info_fields: None,
+        }
+    }
+
+    // Writes `files` below a fresh directory and verifies `torrent` against it
+    fn verify_files(torrent: &Torrent, files: &[(&str, &[u8])]) -> VerifyReport {
+        let dir = std::env::temp_dir().join(format!(
+            "spoiler-verify-{}-{}",
+            std::process::id(),
+            torrent.name
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        for (path, data) in files {
+            let path = dir.join(&torrent.name).join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, data).unwrap();
+        }
+
+        let report = verify(torrent, &dir, &AtomicUsize::new(0));
+        fs::remove_dir_all(&dir).unwrap();
+        report
+    }
+
+    #[test]
+    fn piece_spanning_two_files() {
+        let files: &[(&str, &[u8])] = &[("a", b"abcdef"), ("b", b"ghijkl")];
+        let torrent = torrent("spanning", files);
+
+        let report = verify_files(&torrent, files);
+        assert!(report.bad_pieces.is_empty());
+        assert_eq!(report.files[0].pieces, 2);
+        assert_eq!(report.files[1].pieces, 2);
+        assert!(report.files.iter().all(FileResult::is_ok));
+
+        let report = verify_files(&torrent, &[("a", b"abcdef"), ("b", b"Ghijkl")]);
+        assert_eq!(report.bad_pieces, vec![1]);
+        assert_eq!(report.files[0].bad_pieces, 1);
+        assert_eq!(report.files[1].bad_pieces, 1);
+    }
+
+    #[test]
+    fn missing_middle_file() {
+        let files: &[(&str, &[u8])] = &[("a", b"abcde"), ("b", b"fg"), ("c", b"hijkl")];
+        let torrent = torrent("missing", files);
+
+        let report = verify_files(&torrent, &[files[0], files[2]]);
+        assert_eq!(report.bad_pieces, vec![1]);
+        assert_eq!(report.files[0].status(), "1 of 2 pieces bad");
+        assert_eq!(report.files[1].status(), "Missing");
+        assert_eq!(report.files[2].status(), "1 of 2 pieces bad");
+    }
+
+    #[test]
+    fn short_file() {
+        let files: &[(&str, &[u8])] = &[("a", b"abcdef"), ("b", b"ghijkl")];
+        let torrent = torrent("short", files);
+
+        let report = verify_files(&torrent, &[("a", b"abcd"), files[1]]);
+        assert_eq!(report.bad_pieces, vec![1]);
+        assert_eq!(report.files[0].status(), "Wrong size (4 bytes)");
+        assert_eq!(report.files[1].bad_pieces, 1);
+    }
+
+    #[test]
+    fn short_last_piece() {
+        let files: &[(&str, &[u8])] = &[("a", b"abcdefghij")];
+        let torrent = torrent("last", files);
+        assert_eq!(torrent.pieces.len(), 3);
+
+        let report = verify_files(&torrent, files);
+        assert!(report.bad_pieces.is_empty());
+        assert_eq!(report.files[0].pieces, 3);
+
+        let report = verify_files(&torrent, &[("a", b"abcdefghiJ")]);
+        assert_eq!(report.bad_pieces, vec![2]);
+        assert_eq!(report.bad_ranges(), "2");
+    }
+
+    #[test]
+    fn padding_files_are_zeros() {
+        let files: &[(&str, &[u8])] = &[("a", b"abc"), (".pad/1", &[0]), ("b", b"defg")];
+        let torrent = torrent("padding", files);
+
+        let report = verify_files(&torrent, &[files[0], files[2]]);
+        assert!(report.bad_pieces.is_empty());
+        assert_eq!(report.files.len(), 2);
+        assert!(report.files.iter().all(FileResult::is_ok));
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsStr,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use transmission_rpc::types::TorrentAddArgs;
+
+use crate::{
+    app::{add_torrent_to, App, SharedClient},
+    config::{AfterAdd, WatchDir},
+};
+
+const SCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+// Files are only added once their size stayed the same between two scans, so
+// a browser or script that is still writing one doesn't get it added half way.
+// Files that failed, or were added but could not be cleaned up, are only
+// tried again once they change.
+pub async fn watch(app: Arc<Mutex<App<'static>>>, watch_dirs: Vec<WatchDir>) {
+    let mut sizes: HashMap<PathBuf, u64> = HashMap::new();
+    let mut failed: HashMap<PathBuf, u64> = HashMap::new();
+    let mut done: HashMap<PathBuf, u64> = HashMap::new();
+
+    loop {
+        tokio::time::sleep(SCAN_INTERVAL).await;
+
+        let client = match connected_client(&app) {
+            Some(client) => client,
+            None => continue,
+        };
+
+        let mut seen = HashSet::new();
+        for watch_dir in &watch_dirs {
+            for path in torrent_files(&watch_dir.dir) {
+                let size = match fs::metadata(&path) {
+                    Ok(metadata) => metadata.len(),
+                    Err(_) => continue,
+                };
+                seen.insert(path.to_owned());
+
+                let stable = sizes.insert(path.to_owned(), size) == Some(size);
+                if !stable
+                    || size == 0
+                    || failed.get(&path) == Some(&size)
+                    || done.get(&path) == Some(&size)
+                {
+                    continue;
+                }
+
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+                match add_torrent_to(&client, add_args(watch_dir, &path)).await {
+                    Ok(()) => {
+                        log::info!("Added {} from watch directory {}", name, watch_dir.name);
+                        failed.remove(&path);
+                        if let Err(err) = finish(&watch_dir.after, &path) {
+                            log::error!("Could not clean up {}: {}", name, err);
+                            done.insert(path.to_owned(), size);
+                        }
+                    }
+                    Err(err) => {
+                        log::error!("Could not add {} from watch directory: {}", name, err);
+                        failed.insert(path.to_owned(), size);
+                    }
+                }
+            }
+        }
+
+        sizes.retain(|path, _| seen.contains(path));
+        failed.retain(|path, _| seen.contains(path));
+        done.retain(|path, _| seen.contains(path));
+    }
+}
+
+// Torrents are added to the first daemon, like from the add dialog
+fn connected_client(app: &Arc<Mutex<App<'static>>>) -> Option<SharedClient> {
+    let app = app.lock().unwrap();
+    if !app.connection_state.is_connected() {
+        return None;
+    }
+    Some(app.daemons[0].client.clone())
+}
+
+fn torrent_files(dir: &Path) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file() && path.extension().and_then(OsStr::to_str) == Some("torrent")
+        })
+        .collect()
+}
+
+fn add_args(watch_dir: &WatchDir, path: &Path) -> TorrentAddArgs {
+    TorrentAddArgs {
+        filename: Some(path.to_string_lossy().to_string()),
+        paused: Some(watch_dir.paused),
+        download_dir: watch_dir.download_dir.to_owned(),
+        labels: if watch_dir.labels.is_empty() {
+            None
+        } else {
+            Some(watch_dir.labels.to_owned())
+        },
+        ..TorrentAddArgs::default()
+    }
+}
+
+// Renamed files end in .added, which also keeps them from being picked up again
+fn finish(after: &AfterAdd, path: &Path) -> io::Result<()> {
+    let name = path.file_name().unwrap();
+    match after {
+        AfterAdd::Rename => {
+            let mut added = name.to_owned();
+            added.push(".added");
+            fs::rename(path, path.with_file_name(&added))?;
+            log::info!("Renamed {} to {}", path.display(), added.to_string_lossy());
+        }
+        AfterAdd::Move(dir) => {
+            fs::create_dir_all(dir)?;
+            let target = dir.join(name);
+            // rename doesn't work across file systems
+            if fs::rename(path, &target).is_err() {
+                fs::copy(path, &target)?;
+                fs::remove_file(path)?;
+            }
+            log::info!("Moved {} to {}", path.display(), dir.display());
+        }
+        AfterAdd::Delete => {
+            fs::remove_file(path)?;
+            log::info!("Deleted {}", path.display());
+        }
+    }
+    Ok(())
+}
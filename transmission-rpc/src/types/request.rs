@@ -0,0 +1,426 @@
+use enum_iterator::IntoEnumIterator;
+use serde::{Serialize, Serializer};
+
+#[derive(Serialize, Debug)]
+pub struct RpcRequest {
+    method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    arguments: Option<Args>,
+}
+
+impl RpcRequest {
+    pub fn session_get() -> RpcRequest {
+        RpcRequest {
+            method: String::from("session-get"),
+            arguments: None,
+        }
+    }
+
+    pub fn session_stats() -> RpcRequest {
+        RpcRequest {
+            method: String::from("session-stats"),
+            arguments: None,
+        }
+    }
+
+    pub fn session_close() -> RpcRequest {
+        RpcRequest {
+            method: String::from("session-close"),
+            arguments: None,
+        }
+    }
+
+    pub fn blocklist_update() -> RpcRequest {
+        RpcRequest {
+            method: String::from("blocklist-update"),
+            arguments: None,
+        }
+    }
+
+    pub fn free_space(path: String) -> RpcRequest {
+        RpcRequest {
+            method: String::from("free-space"),
+            arguments: Some(Args::FreeSpaceArgs(FreeSpaceArgs { path })),
+        }
+    }
+
+    pub fn port_test() -> RpcRequest {
+        RpcRequest {
+            method: String::from("port-test"),
+            arguments: None,
+        }
+    }
+
+    pub fn torrent_get(fields: Option<Vec<TorrentGetField>>, ids: Option<Vec<Id>>) -> RpcRequest {
+        let string_fields = fields
+            .unwrap_or(TorrentGetField::all())
+            .iter()
+            .map(|f| f.to_str())
+            .collect();
+        RpcRequest {
+            method: String::from("torrent-get"),
+            arguments: Some(Args::TorrentGetArgs(TorrentGetArgs {
+                fields: Some(string_fields),
+                ids,
+            })),
+        }
+    }
+
+    pub fn torrent_remove(ids: Vec<Id>, delete_local_data: bool) -> RpcRequest {
+        RpcRequest {
+            method: String::from("torrent-remove"),
+            arguments: Some(Args::TorrentRemoveArgs(TorrentRemoveArgs {
+                ids,
+                delete_local_data,
+            })),
+        }
+    }
+
+    pub fn torrent_add(add: TorrentAddArgs) -> RpcRequest {
+        RpcRequest {
+            method: String::from("torrent-add"),
+            arguments: Some(Args::TorrentAddArgs(add)),
+        }
+    }
+
+    pub fn torrent_action(action: TorrentAction, ids: Vec<Id>) -> RpcRequest {
+        RpcRequest {
+            method: action.to_str(),
+            arguments: Some(Args::TorrentActionArgs(TorrentActionArgs { ids })),
+        }
+    }
+
+    pub fn torrent_set(mut args: TorrentSetArgs, ids: Option<Vec<Id>>) -> RpcRequest {
+        args.ids = ids;
+        RpcRequest {
+            method: String::from("torrent-set"),
+            arguments: Some(Args::TorrentSetArgs(args)),
+        }
+    }
+
+    pub fn torrent_set_location(ids: Vec<Id>, location: String, move_from: Option<bool>, ) -> RpcRequest {
+        RpcRequest {
+            method: String::from("torrent-set-location"),
+            arguments: Some(Args::TorrentSetLocationArgs(TorrentSetLocationArgs {
+                ids,
+                location,
+                move_from,
+            })),
+        }
+    }
+
+    pub fn torrent_rename_path(ids: Vec<Id>, path: String, name: String) -> RpcRequest {
+        RpcRequest {
+            method: String::from("torrent-rename-path"),
+            arguments: Some(Args::TorrentRenamePathArgs(TorrentRenamePathArgs {
+                ids,
+                path,
+                name
+            }))
+        }
+    }
+}
+pub trait ArgumentFields {}
+impl ArgumentFields for TorrentGetField {}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Args {
+    FreeSpaceArgs(FreeSpaceArgs),
+    TorrentGetArgs(TorrentGetArgs),
+    TorrentActionArgs(TorrentActionArgs),
+    TorrentRemoveArgs(TorrentRemoveArgs),
+    TorrentAddArgs(TorrentAddArgs),
+    TorrentSetArgs(TorrentSetArgs),
+    TorrentSetLocationArgs(TorrentSetLocationArgs),
+    TorrentRenamePathArgs(TorrentRenamePathArgs),
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct FreeSpaceArgs {
+    path: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TorrentGetArgs {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fields: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ids: Option<Vec<Id>>,
+}
+
+impl Default for TorrentGetArgs {
+    fn default() -> Self {
+        let all_fields = TorrentGetField::into_enum_iter()
+            .map(|it| it.to_str())
+            .collect();
+        TorrentGetArgs {
+            fields: Some(all_fields),
+            ids: None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TorrentActionArgs {
+    ids: Vec<Id>,
+}
+#[derive(Serialize, Debug, Clone)]
+pub struct TorrentRemoveArgs {
+    ids: Vec<Id>,
+    #[serde(rename = "delete-local-data")]
+    delete_local_data: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct TorrentSetArgs {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ids: Option<Vec<Id>>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "files-wanted")]
+    pub files_wanted: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "files-unwanted")]
+    pub files_unwanted: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "priority-high")]
+    pub priority_high: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "priority-low")]
+    pub priority_low: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "priority-normal")]
+    pub priority_normal: Option<Vec<i32>>,
+    /// announce URLs to add to the last tier
+    #[serde(skip_serializing_if = "Option::is_none", rename = "trackerAdd")]
+    pub tracker_add: Option<Vec<String>>,
+    /// ids of the trackers to remove
+    #[serde(skip_serializing_if = "Option::is_none", rename = "trackerRemove")]
+    pub tracker_remove: Option<Vec<i64>>,
+    /// replaces all trackers, an empty string starts a new tier
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        rename = "trackerList",
+        serialize_with = "serialize_tracker_list"
+    )]
+    pub tracker_list: Option<Vec<String>>,
+}
+
+// Transmission expects one announce URL per line, tiers separated by blank lines
+fn serialize_tracker_list<S>(list: &Option<Vec<String>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match list {
+        Some(list) => serializer.serialize_str(&list.join("\n")),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TorrentSetLocationArgs {
+    ids: Vec<Id>,
+    location: String,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "move")]
+    move_from: Option<bool>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TorrentRenamePathArgs {
+    ids: Vec<Id>,
+    path: String,
+    name: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Id {
+    Id(i64),
+    Hash(String),
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TorrentAddArgs {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cookies: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "download-dir")]
+    pub download_dir: Option<String>,
+    /// Either "filename" OR "metainfo" MUST be included
+    /// semi-optional
+    /// filename or URL of the .torrent file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// semi-optional
+    /// base64-encoded .torrent content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metainfo: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paused: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "peer-limit")]
+    pub peer_limit: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none", rename = "bandwidthPriority")]
+    pub bandwidth_priority: Option<i64>,
+    /// list of indices of files to be downloaded
+    /// to ignore some files, put their indices in files_unwanted, otherwise they will still be downloaded
+    #[serde(skip_serializing_if = "Option::is_none", rename = "files-wanted")]
+    pub files_wanted: Option<Vec<i32>>,
+    /// list of indices of files not to download
+    #[serde(skip_serializing_if = "Option::is_none", rename = "files-unwanted")]
+    pub files_unwanted: Option<Vec<i32>>,
+    /// list of indices of files to be downloaded with high priority
+    #[serde(skip_serializing_if = "Option::is_none", rename = "priority-high")]
+    pub priority_high: Option<Vec<i32>>,
+    /// list of indices of files to be downloaded with low priority
+    #[serde(skip_serializing_if = "Option::is_none", rename = "priority-low")]
+    pub priority_low: Option<Vec<i32>>,
+    /// list of indices of files to be downloaded with normal priority
+    #[serde(skip_serializing_if = "Option::is_none", rename = "priority-normal")]
+    pub priority_normal: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<String>>,
+}
+
+impl Default for TorrentAddArgs {
+    fn default() -> Self {
+        TorrentAddArgs {
+            cookies: None,
+            download_dir: None,
+            filename: None,
+            metainfo: None,
+            paused: None,
+            peer_limit: None,
+            bandwidth_priority: None,
+            files_wanted: None,
+            files_unwanted: None,
+            priority_high: None,
+            priority_low: None,
+            priority_normal: None,
+            labels: None,
+        }
+    }
+}
+
+#[derive(Clone, IntoEnumIterator)]
+pub enum TorrentGetField {
+    Id,
+    Addeddate,
+    Comment,
+    Creator,
+    Name,
+    HashString,
+    Totalsize,
+    Donedate,
+    Downloadlimit,
+    Error,
+    Errorstring,
+    Eta,
+    Havevalid,
+    Isfinished,
+    Isstalled,
+    Leftuntildone,
+    Metadatapercentcomplete,
+    Peers,
+    Peersconnected,
+    Peersgettingfromus,
+    Peerssendingtous,
+    Percentdone,
+    Pieces,
+    Piececount,
+    Piecesize,
+    Queueposition,
+    Ratedownload,
+    Rateupload,
+    Recheckprogress,
+    Secondsdownloading,
+    Secondsseeding,
+    Seedratiomode,
+    Seedratiolimit,
+    Sizewhendone,
+    Status,
+    Trackers,
+    Trackerstats,
+    Files,
+    Downloaddir,
+    Uploadedever,
+    Uploadlimit,
+    Uploadratio,
+    Webseedssendingtous,
+    Wanted,
+    Priorities,
+    Filestats
+}
+
+impl TorrentGetField {
+    pub fn all() -> Vec<TorrentGetField> {
+        TorrentGetField::into_enum_iter().collect()
+    }
+}
+
+impl TorrentGetField {
+    pub fn to_str(&self) -> String {
+        match self {
+            TorrentGetField::Id => "id",
+            TorrentGetField::Addeddate => "addedDate",
+            TorrentGetField::Comment => "comment",
+            TorrentGetField::Creator => "creator",
+            TorrentGetField::Name => "name",
+            TorrentGetField::HashString => "hashString",
+            TorrentGetField::Totalsize => "totalSize",
+            TorrentGetField::Donedate => "doneDate",
+            TorrentGetField::Downloadlimit => "downloadLimit",
+            TorrentGetField::Error => "error",
+            TorrentGetField::Errorstring => "errorString",
+            TorrentGetField::Eta => "eta",
+            TorrentGetField::Havevalid => "haveValid",
+            TorrentGetField::Isfinished => "isFinished",
+            TorrentGetField::Isstalled => "isStalled",
+            TorrentGetField::Leftuntildone => "leftUntilDone",
+            TorrentGetField::Metadatapercentcomplete => "metadataPercentComplete",
+            TorrentGetField::Peers => "peers",
+            TorrentGetField::Peersconnected => "peersConnected",
+            TorrentGetField::Peersgettingfromus => "peersGettingFromUs",
+            TorrentGetField::Peerssendingtous => "peersSendingToUs",
+            TorrentGetField::Percentdone => "percentDone",
+            TorrentGetField::Pieces => "pieces",
+            TorrentGetField::Piececount => "pieceCount",
+            TorrentGetField::Piecesize => "pieceSize",
+            TorrentGetField::Queueposition => "queuePosition",
+            TorrentGetField::Ratedownload => "rateDownload",
+            TorrentGetField::Rateupload => "rateUpload",
+            TorrentGetField::Recheckprogress => "recheckProgress",
+            TorrentGetField::Secondsdownloading => "secondsDownloading",
+            TorrentGetField::Secondsseeding => "secondsSeeding",
+            TorrentGetField::Seedratiomode => "seedRatioMode",
+            TorrentGetField::Seedratiolimit => "seedRatioLimit",
+            TorrentGetField::Sizewhendone => "sizeWhenDone",
+            TorrentGetField::Status => "status",
+            TorrentGetField::Trackers => "trackers",
+            TorrentGetField::Trackerstats => "trackerStats",
+            TorrentGetField::Files => "files",
+            TorrentGetField::Downloaddir => "downloadDir",
+            TorrentGetField::Uploadedever => "uploadedEver",
+            TorrentGetField::Uploadlimit => "uploadLimit",
+            TorrentGetField::Uploadratio => "uploadRatio",
+            TorrentGetField::Webseedssendingtous => "webseedsSendingToUs",
+            TorrentGetField::Wanted => "wanted",
+            TorrentGetField::Priorities => "priorities",
+            TorrentGetField::Filestats => "fileStats",
+        }.to_string()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum TorrentAction {
+    Start,
+    Stop,
+    StartNow,
+    Verify,
+    Reannounce,
+}
+
+impl TorrentAction {
+    pub fn to_str(&self) -> String {
+        match self {
+            TorrentAction::Start => "torrent-start",
+            TorrentAction::Stop => "torrent-stop",
+            TorrentAction::StartNow => "torrent-start-now",
+            TorrentAction::Verify => "torrent-verify",
+            TorrentAction::Reannounce => "torrent-reannounce",
+        }.to_string()
+    }
+}